@@ -0,0 +1,113 @@
+//! Times height map generation over hexagonal grids of growing radius.
+//!
+//! Compares the permutation table perlin noise against the old generator,
+//! which hashed the stringified lattice point twice for every corner of every sample.
+//!
+//! cargo run --release --example height_bench
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use xxhash_rust::xxh3::xxh3_64_with_seed;
+
+use magic_game::hex::HexCoord;
+use magic_game::plugins::world_3d::hex::height_map::{HeightGenerator, PerlinGenerator};
+
+const RADII: [i32; 4] = [20, 50, 100, 200];
+const SEED: u64 = 42;
+
+fn main() {
+    println!("{:>8} {:>10} {:>14} {:>14} {:>9}", "radius", "tiles", "old", "new", "speedup");
+    for radius in RADII {
+        let tiles: Vec<HexCoord> = HexCoord::ZERO.spiral(radius).collect();
+        let old = time(&tiles, &LegacyPerlin::dunes(SEED));
+        let new = time(&tiles, &PerlinGenerator::dunes(Some(SEED)));
+        println!(
+            "{:>8} {:>10} {:>14?} {:>14?} {:>8.1}x",
+            radius,
+            tiles.len(),
+            old,
+            new,
+            old.as_secs_f64() / new.as_secs_f64()
+        );
+    }
+}
+
+/// Best of a few runs so one slow run doesn't skew the results
+fn time(tiles: &[HexCoord], generator: &impl HeightGenerator) -> Duration {
+    (0..3)
+        .map(|_| {
+            let start = Instant::now();
+            for tile in tiles {
+                black_box(generator.generate_height(*tile));
+            }
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Legacy ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+/// The perlin generator as it was before the permutation table, kept here for comparison
+struct LegacyPerlin {
+    /// (x_freq, y_freq, magnitude) of each step
+    steps: Vec<(f32, f32, f32)>,
+    seed: u64
+}
+
+impl LegacyPerlin {
+    /// Same steps as `PerlinGenerator::dunes`
+    fn dunes(seed: u64) -> Self {
+        Self { steps: vec![(0.05, 0.01, 30.), (0.5, 0.1, 1.)], seed }
+    }
+
+    fn seeded_hash(bytes: &[u8], seed: u64, msg: &str) -> u64 {
+        let mut vec = bytes.to_vec();
+        vec.append(&mut msg.as_bytes().to_vec());
+        xxh3_64_with_seed(vec.as_slice(), seed)
+    }
+
+    fn gradient(&self, vec: Vec2) -> Vec2 {
+        let x_dir = Self::seeded_hash(vec.to_string().as_bytes(), self.seed, "Perlin X Dir") as f32;
+        let y_dir = Self::seeded_hash(vec.to_string().as_bytes(), self.seed, "Perlin Y Dir") as f32;
+        Vec2::new(x_dir, y_dir).normalize()
+    }
+
+    fn fade(p: f32) -> f32 {
+        p*p*p*(p*(p*6. - 15.) + 10.)
+    }
+
+    fn noise(&self, v: Vec2) -> f32 {
+        let v0 = v.floor();
+        let v1 = v0 + Vec2::new(1.,0.);
+        let v2 = v0 + Vec2::new(0.,1.);
+        let v3 = v0 + Vec2::new(1.,1.);
+
+        let g0 = self.gradient(v0);
+        let g1 = self.gradient(v1);
+        let g2 = self.gradient(v2);
+        let g3 = self.gradient(v3);
+
+        let fade_t0 = Self::fade(v.x - v0.x);
+        let fade_t1 = Self::fade(v.y - v0.y);
+
+        let v0v1 = (1. - fade_t0) * g0.dot(v - v0) + fade_t0 * g1.dot(v - v1);
+        let v2v3 = (1. - fade_t0) * g2.dot(v - v2) + fade_t0 * g3.dot(v - v3);
+
+        (1. - fade_t1) * v0v1 + fade_t1 * v2v3
+    }
+}
+
+impl HeightGenerator for LegacyPerlin {
+    fn generate_height(&self, coord: HexCoord) -> u32 {
+        let mut height = 0.;
+        for (x_freq, y_freq, magnitude) in self.steps.iter() {
+            let noise = self.noise(Vec2::new(coord.0 as f32 * x_freq, coord.1 as f32 * y_freq));
+            height += (noise * 2. + 0.7) * magnitude;
+            height += noise * magnitude;
+        }
+        height as u32
+    }
+}
@@ -0,0 +1,290 @@
+//! Hex grid math shared by the 2d and 3d worlds.
+//!
+//! Everything in here is in hex space. Converting to and from world space
+//! goes through a [`HexLayout`], which knows the tile size and orientation.
+pub mod direction;
+pub mod iter;
+pub mod layout;
+pub mod line;
+pub mod offset;
+pub mod pathfinding;
+pub mod region;
+pub mod shape;
+pub mod wrap;
+
+// Standard Lib Imports
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+// Bevy Imports
+use bevy::prelude::*;
+use bevy_inspector_egui::Inspectable;
+
+pub use direction::HexDirection;
+pub use iter::{HexRange, HexRing, HexSpiral};
+pub use layout::{HexLayout, HexOrientation, WorldPlane};
+pub use line::LineStep;
+pub use offset::{DoubledCoord, DoubledKind, OffsetCoord, OffsetKind};
+pub use pathfinding::{HexGraph, HexPath, ReachableArea};
+pub use region::HexRegion;
+pub use shape::HexShape;
+pub use wrap::HexWrap;
+
+#[derive(Component, Inspectable, Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+/// Coordinates in axial space
+/// see: https://www.redblobgames.com/grids/hexagons/#coordinates-axial
+/// HexCoord(q, r)
+pub struct HexCoord(pub i32, pub i32);
+
+impl HexCoord {
+    pub const ZERO: HexCoord = HexCoord(0, 0);
+
+    /// Builds from cube coordinates. q + r + s must be 0
+    /// see: https://www.redblobgames.com/grids/hexagons/#coordinates-cube
+    pub fn from_cube(q: i32, r: i32, s: i32) -> HexCoord {
+        debug_assert_eq!(q + r + s, 0, "cube coords must sum to zero");
+        HexCoord(q, r)
+    }
+
+    pub fn q(&self) -> i32 {
+        self.0
+    }
+
+    pub fn r(&self) -> i32 {
+        self.1
+    }
+
+    /// Third cube coordinate, derived from q and r
+    pub fn s(&self) -> i32 {
+        -self.0 - self.1
+    }
+
+    pub fn to_offset(&self, kind: OffsetKind) -> OffsetCoord {
+        OffsetCoord::from_hex(*self, kind)
+    }
+
+    pub fn from_offset(coord: OffsetCoord, kind: OffsetKind) -> HexCoord {
+        coord.to_hex(kind)
+    }
+
+    pub fn to_doubled(&self, kind: DoubledKind) -> DoubledCoord {
+        DoubledCoord::from_hex(*self, kind)
+    }
+
+    pub fn from_doubled(coord: DoubledCoord, kind: DoubledKind) -> HexCoord {
+        coord.to_hex(kind)
+    }
+
+    /// Round floating point hex space coords to integer hexcoord
+    /// see: https://www.redblobgames.com/grids/hexagons/#rounding
+    pub fn from_floating((fx, fy): (f32, f32)) -> HexCoord {
+        let mut x = fx.round();
+        let mut y = fy.round();
+        let rem_x = fx - x;
+        let rem_y = fy - y;
+        if rem_x.abs() >= rem_y.abs() {
+            x += (rem_x + 0.5*rem_y).round();
+        } else {
+            y += (rem_y + 0.5*rem_x).round();
+        }
+        HexCoord(x as i32, y as i32)
+    }
+
+    pub fn to_bytes(self) -> [u8; 8] {
+        let x:[u8; 4] = self.0.to_ne_bytes();
+        let y:[u8; 4] = self.1.to_ne_bytes();
+        let concat = [x[0],x[1],x[2],x[3],y[0],y[1],y[2],y[3]];
+        concat
+    }
+
+    /// Distance in hex space to other coord.
+    /// See: https://www.redblobgames.com/grids/hexagons/#distances-axial
+    pub fn distance(&self, other: HexCoord) -> u64 {
+        (*self - other).length()
+    }
+
+    /// Distance from HexCoord(0,0)
+    pub fn length(&self) -> u64 {
+        (self.q().abs() + self.r().abs() + self.s().abs()) as u64 / 2
+    }
+
+    pub fn neighbor(&self, direction: HexDirection) -> HexCoord {
+        *self + direction.offset()
+    }
+
+    /// Neighbors in the same order as `HexDirection::ALL`
+    pub fn neighbors(&self) -> [HexCoord; 6] {
+        HexDirection::ALL.map(|direction| self.neighbor(direction))
+    }
+
+    /// The six tiles two steps away that touch self only at a corner
+    /// See: https://www.redblobgames.com/grids/hexagons/#neighbors-diagonal
+    pub fn diagonal_neighbors(&self) -> [HexCoord; 6] {
+        HexDirection::ALL.map(|direction| *self + direction.diagonal_offset())
+    }
+
+    /// Rotates around center by `steps` sixths of a turn counter clockwise.
+    /// Negative steps rotate clockwise.
+    /// See: https://www.redblobgames.com/grids/hexagons/#rotation
+    pub fn rotate_around(&self, center: HexCoord, steps: i32) -> HexCoord {
+        let mut rel = *self - center;
+        for _ in 0..steps.rem_euclid(6) {
+            rel = HexCoord::from_cube(-rel.s(), -rel.q(), -rel.r());
+        }
+        rel + center
+    }
+
+    /// Mirrors across the line through center along which `axis` stays constant
+    /// See: https://www.redblobgames.com/grids/hexagons/#reflection
+    pub fn reflect(&self, axis: HexAxis, center: HexCoord) -> HexCoord {
+        let rel = *self - center;
+        let reflected = match axis {
+            HexAxis::Q => HexCoord::from_cube(rel.q(), rel.s(), rel.r()),
+            HexAxis::R => HexCoord::from_cube(rel.s(), rel.r(), rel.q()),
+            HexAxis::S => HexCoord::from_cube(rel.r(), rel.q(), rel.s()),
+        };
+        reflected + center
+    }
+
+    /// Gets the hexcoords that draw a straight line between self and other.
+    /// Includes both ends and each tile is adjacent to the one before it.
+    /// Where the line runs exactly along an edge the same side is always picked
+    /// See: https://www.redblobgames.com/grids/hexagons/#line-drawing
+    pub fn line_between(&self, other: HexCoord) -> Vec<HexCoord> {
+        line::line(*self, other)
+    }
+
+    /// Same as `line_between` but keeps both tiles wherever the line runs along an edge
+    pub fn line_steps(&self, other: HexCoord) -> Vec<LineStep> {
+        line::line_steps(*self, other)
+    }
+
+    /// Every tile touched by the line between self and other,
+    /// including both sides of edges it runs along. Use for line of sight
+    pub fn supercover_line(&self, other: HexCoord) -> Vec<HexCoord> {
+        line::supercover(*self, other)
+    }
+
+    /// returns all the hex coords that are
+    /// within radius number of tiles
+    pub fn within_radius(&self, radius: i32) -> HexRange {
+        HexRange::new(*self, radius)
+    }
+
+    /// returns the hex coords exactly radius tiles away
+    pub fn ring(&self, radius: i32) -> HexRing {
+        HexRing::new(*self, radius)
+    }
+
+    /// returns all the hex coords within radius number of tiles,
+    /// starting at self and moving outward ring by ring
+    pub fn spiral(&self, radius: i32) -> HexSpiral {
+        HexSpiral::new(*self, radius)
+    }
+}
+
+/// The three cube axes
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum HexAxis {
+    Q,
+    R,
+    S
+}
+
+impl Add for HexCoord {
+    type Output = HexCoord;
+    fn add(self, rhs: HexCoord) -> HexCoord {
+        HexCoord(self.0 + rhs.0, self.1 + rhs.1)
+    }
+}
+
+impl AddAssign for HexCoord {
+    fn add_assign(&mut self, rhs: HexCoord) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for HexCoord {
+    type Output = HexCoord;
+    fn sub(self, rhs: HexCoord) -> HexCoord {
+        HexCoord(self.0 - rhs.0, self.1 - rhs.1)
+    }
+}
+
+impl SubAssign for HexCoord {
+    fn sub_assign(&mut self, rhs: HexCoord) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<i32> for HexCoord {
+    type Output = HexCoord;
+    fn mul(self, rhs: i32) -> HexCoord {
+        HexCoord(self.0 * rhs, self.1 * rhs)
+    }
+}
+
+impl Neg for HexCoord {
+    type Output = HexCoord;
+    fn neg(self) -> HexCoord {
+        HexCoord(-self.0, -self.1)
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Tests ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance() {
+        assert_eq!(HexCoord(0, 0).distance(HexCoord(0, 0)), 0);
+        assert_eq!(HexCoord(0, 0).distance(HexCoord(3, -1)), 3);
+        assert_eq!(HexCoord(2, 1).distance(HexCoord(-1, 3)), 3);
+        assert_eq!(HexCoord(-2, -2).distance(HexCoord(2, 2)), 8);
+        assert_eq!(HexCoord(4, -7).length(), 7);
+    }
+
+    #[test]
+    fn neighbors() {
+        assert_eq!(HexCoord(2, -1).neighbors(), [
+            HexCoord(3, -1),
+            HexCoord(3, -2),
+            HexCoord(2, -2),
+            HexCoord(1, -1),
+            HexCoord(1, 0),
+            HexCoord(2, 0),
+        ]);
+        for neighbor in HexCoord(2, -1).neighbors() {
+            assert_eq!(neighbor.distance(HexCoord(2, -1)), 1);
+        }
+        for diagonal in HexCoord(2, -1).diagonal_neighbors() {
+            assert_eq!(diagonal.distance(HexCoord(2, -1)), 2);
+        }
+    }
+
+    #[test]
+    fn rotate_around() {
+        let center = HexCoord(1, 1);
+        // one sixth counter clockwise turns east into north east
+        assert_eq!(HexCoord(2, 1).rotate_around(center, 1), HexCoord(2, 0));
+        assert_eq!(HexCoord(3, 0).rotate_around(center, 1), HexCoord(2, -1));
+        assert_eq!(HexCoord(3, 0).rotate_around(center, 3), HexCoord(-1, 2));
+        assert_eq!(HexCoord(3, 0).rotate_around(center, -1), HexCoord(2, 2));
+        assert_eq!(HexCoord(3, 0).rotate_around(center, 6), HexCoord(3, 0));
+        assert_eq!(center.rotate_around(center, 2), center);
+    }
+
+    #[test]
+    fn reflect() {
+        let coord = HexCoord(1, 2);
+        assert_eq!(coord.reflect(HexAxis::Q, HexCoord::ZERO), HexCoord(1, -3));
+        assert_eq!(coord.reflect(HexAxis::R, HexCoord::ZERO), HexCoord(-3, 2));
+        assert_eq!(coord.reflect(HexAxis::S, HexCoord::ZERO), HexCoord(2, 1));
+        // around another center
+        assert_eq!(HexCoord(3, 1).reflect(HexAxis::Q, HexCoord(1, 1)), HexCoord(3, -1));
+        for axis in [HexAxis::Q, HexAxis::R, HexAxis::S] {
+            assert_eq!(coord.reflect(axis, HexCoord(2, -1)).reflect(axis, HexCoord(2, -1)), coord);
+        }
+    }
+}
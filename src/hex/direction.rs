@@ -0,0 +1,69 @@
+//! The six directions from a tile to its neighbors.
+//! See: https://www.redblobgames.com/grids/hexagons/#neighbors-axial
+use crate::hex::HexCoord;
+
+/// Directions as seen on a pointy layout in the XZ plane from the default camera,
+/// where increasing r heads south.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum HexDirection {
+    East,
+    NorthEast,
+    NorthWest,
+    West,
+    SouthWest,
+    SouthEast
+}
+
+impl HexDirection {
+    /// All directions in counter clockwise order starting at East
+    pub const ALL: [HexDirection; 6] = [
+        HexDirection::East,
+        HexDirection::NorthEast,
+        HexDirection::NorthWest,
+        HexDirection::West,
+        HexDirection::SouthWest,
+        HexDirection::SouthEast,
+    ];
+
+    /// Position of this direction in `ALL`
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// Wraps any integer onto one of the six directions
+    pub fn from_index(index: i32) -> HexDirection {
+        Self::ALL[index.rem_euclid(6) as usize]
+    }
+
+    /// Offset from a tile to its neighbor in this direction
+    pub fn offset(&self) -> HexCoord {
+        match self {
+            HexDirection::East => HexCoord(1, 0),
+            HexDirection::NorthEast => HexCoord(1, -1),
+            HexDirection::NorthWest => HexCoord(0, -1),
+            HexDirection::West => HexCoord(-1, 0),
+            HexDirection::SouthWest => HexCoord(-1, 1),
+            HexDirection::SouthEast => HexCoord(0, 1),
+        }
+    }
+
+    /// Offset to the diagonal neighbor that sits between this direction and the next counter clockwise one
+    /// See: https://www.redblobgames.com/grids/hexagons/#neighbors-diagonal
+    pub fn diagonal_offset(&self) -> HexCoord {
+        self.offset() + self.rotate_ccw(1).offset()
+    }
+
+    /// Rotates by `steps` sixths of a turn counter clockwise
+    pub fn rotate_ccw(&self, steps: i32) -> HexDirection {
+        Self::from_index(self.index() as i32 + steps)
+    }
+
+    /// Rotates by `steps` sixths of a turn clockwise
+    pub fn rotate_cw(&self, steps: i32) -> HexDirection {
+        Self::from_index(self.index() as i32 - steps)
+    }
+
+    pub fn opposite(&self) -> HexDirection {
+        self.rotate_ccw(3)
+    }
+}
@@ -0,0 +1,228 @@
+//! Allocation free iterators over neighborhoods of tiles.
+//! See: https://www.redblobgames.com/grids/hexagons/#rings
+use std::cmp::{max,min};
+use std::iter::FusedIterator;
+
+use crate::hex::{HexCoord, HexDirection};
+
+/// Number of tiles within `radius` of a tile, including itself
+pub fn hexagon_area(radius: i32) -> usize {
+    if radius < 0 {
+        return 0;
+    }
+    let radius = radius as usize;
+    1 + 3 * radius * (radius + 1)
+}
+
+/// Number of tiles exactly `radius` away from a tile
+pub fn ring_len(radius: i32) -> usize {
+    match radius {
+        r if r < 0 => 0,
+        0 => 1,
+        r => 6 * r as usize
+    }
+}
+
+/// Tiles exactly `radius` away from a center, walking counter clockwise from the south west corner.
+/// Built by `HexCoord::ring`
+#[derive(Debug, Clone)]
+pub struct HexRing {
+    current: HexCoord,
+    radius: i32,
+    /// index into `HexDirection::ALL` of the side currently being walked
+    side: usize,
+    /// steps already taken along the current side
+    step: i32,
+    remaining: usize
+}
+
+impl HexRing {
+    pub fn new(center: HexCoord, radius: i32) -> Self {
+        let start = center + HexDirection::SouthWest.offset() * max(radius, 0);
+        Self {
+            current: start,
+            radius,
+            side: 0,
+            step: 0,
+            remaining: ring_len(radius)
+        }
+    }
+}
+
+impl Iterator for HexRing {
+    type Item = HexCoord;
+
+    fn next(&mut self) -> Option<HexCoord> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let coord = self.current;
+        if self.remaining > 0 {
+            self.current = self.current.neighbor(HexDirection::ALL[self.side]);
+            self.step += 1;
+            if self.step == self.radius {
+                self.step = 0;
+                self.side += 1;
+            }
+        }
+        Some(coord)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for HexRing {}
+impl FusedIterator for HexRing {}
+
+/// Tiles within `radius` of a center, from the center outward ring by ring.
+/// Built by `HexCoord::spiral`
+#[derive(Debug, Clone)]
+pub struct HexSpiral {
+    center: HexCoord,
+    max_radius: i32,
+    ring: HexRing,
+    remaining: usize
+}
+
+impl HexSpiral {
+    pub fn new(center: HexCoord, radius: i32) -> Self {
+        let remaining = hexagon_area(radius);
+        Self {
+            center,
+            max_radius: radius,
+            ring: HexRing::new(center, if remaining == 0 { -1 } else { 0 }),
+            remaining
+        }
+    }
+}
+
+impl Iterator for HexSpiral {
+    type Item = HexCoord;
+
+    fn next(&mut self) -> Option<HexCoord> {
+        if self.remaining == 0 {
+            return None;
+        }
+        if self.ring.len() == 0 && self.ring.radius < self.max_radius {
+            self.ring = HexRing::new(self.center, self.ring.radius + 1);
+        }
+        let coord = self.ring.next()?;
+        self.remaining -= 1;
+        Some(coord)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for HexSpiral {}
+impl FusedIterator for HexSpiral {}
+
+/// Tiles within `radius` of a center, column by column in increasing q then r.
+/// Built by `HexCoord::within_radius`
+/// See: https://www.redblobgames.com/grids/hexagons/#range-coordinate
+#[derive(Debug, Clone)]
+pub struct HexRange {
+    center: HexCoord,
+    radius: i32,
+    /// offset from center of the next tile
+    q: i32,
+    r: i32,
+    remaining: usize
+}
+
+impl HexRange {
+    pub fn new(center: HexCoord, radius: i32) -> Self {
+        Self {
+            center,
+            radius,
+            // the first column always starts level with the center
+            q: -radius,
+            r: 0,
+            remaining: hexagon_area(radius)
+        }
+    }
+}
+
+impl Iterator for HexRange {
+    type Item = HexCoord;
+
+    fn next(&mut self) -> Option<HexCoord> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let coord = self.center + HexCoord(self.q, self.r);
+        if self.r < min(self.radius, -self.q + self.radius) {
+            self.r += 1;
+        } else {
+            self.q += 1;
+            self.r = max(-self.radius, -self.q - self.radius);
+        }
+        Some(coord)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for HexRange {}
+impl FusedIterator for HexRange {}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Tests ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    const RADII: [i32; 3] = [0, 1, 5];
+
+    /// Checks the size hint matches what is left at every step, not only at the start
+    fn assert_exact_size(mut iter: impl Iterator<Item = HexCoord>, expected: usize) {
+        let mut remaining = expected;
+        loop {
+            assert_eq!(iter.size_hint(), (remaining, Some(remaining)));
+            if iter.next().is_none() {
+                break;
+            }
+            remaining -= 1;
+        }
+        assert_eq!(remaining, 0);
+    }
+
+    #[test]
+    fn ring_size_hint() {
+        let center = HexCoord(2, -3);
+        for radius in RADII {
+            assert_exact_size(center.ring(radius), ring_len(radius));
+            let tiles: HashSet<HexCoord> = center.ring(radius).collect();
+            assert_eq!(tiles.len(), ring_len(radius));
+            assert!(tiles.iter().all(|tile| tile.distance(center) == radius as u64));
+        }
+    }
+
+    #[test]
+    fn spiral_size_hint() {
+        let center = HexCoord(2, -3);
+        for radius in RADII {
+            assert_exact_size(center.spiral(radius), hexagon_area(radius));
+            let tiles: HashSet<HexCoord> = center.spiral(radius).collect();
+            assert_eq!(tiles.len(), hexagon_area(radius));
+            assert!(tiles.iter().all(|tile| tile.distance(center) <= radius as u64));
+        }
+    }
+
+    #[test]
+    fn counts() {
+        assert_eq!(ring_len(5), 30);
+        assert_eq!(hexagon_area(5), 91);
+        assert_eq!(HexCoord::ZERO.within_radius(5).count(), 91);
+    }
+}
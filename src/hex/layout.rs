@@ -0,0 +1,228 @@
+//! Conversions between hex space and world space.
+//! See: https://www.redblobgames.com/grids/hexagons/implementation.html#layout
+use bevy::prelude::*;
+
+use crate::hex::{HexCoord, HexDirection};
+
+/// Which way the corners of a tile point
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HexOrientation {
+    /// Corner at the top. Rows of tiles run along the x axis
+    Pointy,
+    /// Flat edge at the top. Columns of tiles run along the y axis
+    Flat
+}
+
+impl HexOrientation {
+    /// Forward matrix (hex -> pixel) as [f0, f1, f2, f3]
+    fn forward(&self) -> [f32; 4] {
+        let sqrt3 = f32::sqrt(3.0);
+        match self {
+            HexOrientation::Pointy => [sqrt3, sqrt3 / 2.0, 0.0, 3.0 / 2.0],
+            HexOrientation::Flat => [3.0 / 2.0, 0.0, sqrt3 / 2.0, sqrt3],
+        }
+    }
+
+    /// Inverse matrix (pixel -> hex) as [b0, b1, b2, b3]
+    fn backward(&self) -> [f32; 4] {
+        let sqrt3 = f32::sqrt(3.0);
+        match self {
+            HexOrientation::Pointy => [sqrt3 / 3.0, -1.0 / 3.0, 0.0, 2.0 / 3.0],
+            HexOrientation::Flat => [2.0 / 3.0, 0.0, -1.0 / 3.0, sqrt3 / 3.0],
+        }
+    }
+
+    /// Rotation around the plane normal needed to turn a pointy topped tile into this orientation
+    pub fn tile_angle(&self) -> f32 {
+        match self {
+            HexOrientation::Pointy => 0.0,
+            HexOrientation::Flat => std::f32::consts::FRAC_PI_6,
+        }
+    }
+}
+
+/// Which plane of world space the grid lies in
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WorldPlane {
+    /// Hex space maps onto x and y. Used by the 2d world
+    XY,
+    /// Hex space maps onto x and z, leaving y for height. Used by the 3d world
+    XZ
+}
+
+#[derive(Resource, Debug, Copy, Clone, PartialEq)]
+/// Describes how hex space is laid out in world space.
+///
+/// Insert as a resource to change tile size or orientation at runtime.
+/// Tiles are moved and resized to match and 3d pieces are put back on the tile they stood on.
+pub struct HexLayout {
+    pub orientation: HexOrientation,
+    /// distance from the center of a tile to its corners
+    pub size: f32,
+    /// position of HexCoord(0,0) within the plane
+    pub origin: Vec2,
+    pub plane: WorldPlane
+}
+
+impl HexLayout {
+    pub fn new(orientation: HexOrientation, size: f32, plane: WorldPlane) -> Self {
+        Self { orientation, size, origin: Vec2::ZERO, plane }
+    }
+
+    pub fn with_origin(mut self, origin: Vec2) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// distance from the center of a tile to the middle of its edges
+    pub fn inner_radius(&self) -> f32 {
+        self.size * f32::sqrt(3.0) / 2.0
+    }
+
+    /// distance between two opposite edges
+    pub fn small_diameter(&self) -> f32 {
+        2.0 * self.inner_radius()
+    }
+
+    /// distance between two opposite corners
+    pub fn large_diameter(&self) -> f32 {
+        2.0 * self.size
+    }
+
+    /// Position of the center of coord within the plane
+    /// see: https://www.redblobgames.com/grids/hexagons/#hex-to-pixel-axial
+    pub fn to_pixel(&self, coord: HexCoord) -> Vec2 {
+        let [f0, f1, f2, f3] = self.orientation.forward();
+        let (q, r) = (coord.0 as f32, coord.1 as f32);
+        let x = (f0 * q + f1 * r) * self.size;
+        let y = (f2 * q + f3 * r) * self.size;
+        Vec2::new(x, y) + self.origin
+    }
+
+    /// Unrounded position in hex space of a point within the plane
+    /// see: https://www.redblobgames.com/grids/hexagons/#pixel-to-hex
+    pub fn pixel_to_floating(&self, pixel: Vec2) -> (f32, f32) {
+        let [b0, b1, b2, b3] = self.orientation.backward();
+        let point = (pixel - self.origin) / self.size;
+        let q = b0 * point.x + b1 * point.y;
+        let r = b2 * point.x + b3 * point.y;
+        (q, r)
+    }
+
+    pub fn from_pixel(&self, pixel: Vec2) -> HexCoord {
+        HexCoord::from_floating(self.pixel_to_floating(pixel))
+    }
+
+    /// Position of the center of coord in world space.
+    /// The axis not covered by the plane is set to 0
+    pub fn to_world(&self, coord: HexCoord) -> Vec3 {
+        let pixel = self.to_pixel(coord);
+        match self.plane {
+            WorldPlane::XY => Vec3::new(pixel.x, pixel.y, 0.),
+            WorldPlane::XZ => Vec3::new(pixel.x, 0., pixel.y),
+        }
+    }
+
+    /// Projects world coord onto the plane and rounds it to the tile it lands in
+    pub fn from_world(&self, world_coord: Vec3) -> HexCoord {
+        self.from_pixel(self.project(world_coord))
+    }
+
+    /// Drops the axis not covered by the plane
+    pub fn project(&self, world_coord: Vec3) -> Vec2 {
+        match self.plane {
+            WorldPlane::XY => Vec2::new(world_coord.x, world_coord.y),
+            WorldPlane::XZ => Vec2::new(world_coord.x, world_coord.z),
+        }
+    }
+
+    /// Unit vector in world space pointing from a tile toward its neighbor in direction
+    pub fn direction_to_world(&self, direction: HexDirection) -> Vec3 {
+        let neighbor = HexCoord::ZERO.neighbor(direction);
+        (self.to_world(neighbor) - self.to_world(HexCoord::ZERO)).normalize()
+    }
+
+    /// The direction closest to pointing along a world space offset
+    pub fn nearest_direction(&self, world_offset: Vec3) -> HexDirection {
+        let offset = self.project(world_offset);
+        let alignment = |direction: &HexDirection| {
+            self.project(self.direction_to_world(*direction)).dot(offset)
+        };
+        HexDirection::ALL
+            .into_iter()
+            .max_by(|a, b| alignment(a).total_cmp(&alignment(b)))
+            .unwrap()
+    }
+
+    /// Rotation turning a piece to face direction.
+    /// Pieces face -z in the XZ plane like bevy cameras, and +y in the XY plane
+    pub fn facing(&self, direction: HexDirection) -> Quat {
+        let dir = self.direction_to_world(direction);
+        match self.plane {
+            WorldPlane::XY => Quat::from_rotation_z(f32::atan2(-dir.x, dir.y)),
+            WorldPlane::XZ => Quat::from_rotation_y(f32::atan2(-dir.x, -dir.z)),
+        }
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Tests ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIENTATIONS: [HexOrientation; 2] = [HexOrientation::Pointy, HexOrientation::Flat];
+    const PLANES: [WorldPlane; 2] = [WorldPlane::XY, WorldPlane::XZ];
+
+    /// Every orientation and plane, away from the origin and at an odd size
+    fn layouts() -> Vec<HexLayout> {
+        ORIENTATIONS.into_iter()
+            .flat_map(|orientation| PLANES.map(|plane| {
+                HexLayout::new(orientation, 1.7, plane).with_origin(Vec2::new(3., -2.))
+            }))
+            .collect()
+    }
+
+    #[test]
+    fn pixel_round_trip() {
+        for layout in layouts() {
+            for coord in HexCoord::ZERO.spiral(5) {
+                let pixel = layout.to_pixel(coord);
+                assert_eq!(layout.from_pixel(pixel), coord, "{:?}", layout);
+                // anywhere inside the tile rounds to it
+                for direction in HexDirection::ALL {
+                    let nudge = layout.project(layout.direction_to_world(direction)) * layout.inner_radius() * 0.9;
+                    assert_eq!(layout.from_pixel(pixel + nudge), coord, "{:?}", layout);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn world_round_trip() {
+        for layout in layouts() {
+            for coord in HexCoord::ZERO.spiral(5) {
+                let world = layout.to_world(coord);
+                assert_eq!(layout.from_world(world), coord, "{:?}", layout);
+                // the axis off the plane is ignored
+                let off_plane = match layout.plane {
+                    WorldPlane::XY => Vec3::Z,
+                    WorldPlane::XZ => Vec3::Y
+                };
+                assert_eq!(layout.from_world(world + off_plane * 4.), coord, "{:?}", layout);
+            }
+        }
+    }
+
+    #[test]
+    fn to_world_uses_plane() {
+        let sqrt3 = f32::sqrt(3.);
+        let pointy = HexLayout::new(HexOrientation::Pointy, 1., WorldPlane::XY);
+        assert!(pointy.to_world(HexCoord(1, 0)).abs_diff_eq(Vec3::new(sqrt3, 0., 0.), 1e-5));
+        assert!(pointy.to_world(HexCoord(0, 1)).abs_diff_eq(Vec3::new(sqrt3 / 2., 1.5, 0.), 1e-5));
+
+        let flat = HexLayout::new(HexOrientation::Flat, 1., WorldPlane::XZ);
+        assert!(flat.to_world(HexCoord(1, 0)).abs_diff_eq(Vec3::new(1.5, 0., sqrt3 / 2.), 1e-5));
+        assert!(flat.to_world(HexCoord(0, 1)).abs_diff_eq(Vec3::new(0., 0., sqrt3), 1e-5));
+    }
+}
@@ -0,0 +1,150 @@
+//! Line drawing in cube space.
+//! See: https://www.redblobgames.com/grids/hexagons/#line-drawing
+use crate::hex::HexCoord;
+
+/// Offset added to both ends of a line so points landing exactly on
+/// an edge between two tiles consistently round to the same side.
+/// Components sum to zero so the nudged point stays on the cube plane.
+const NUDGE: (f64, f64, f64) = (1e-6, 2e-6, -3e-6);
+
+/// One tile along a line.
+/// Lines that run exactly along the edge between two tiles touch both of them equally
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LineStep {
+    Single(HexCoord),
+    Tie(HexCoord, HexCoord)
+}
+
+impl LineStep {
+    /// The tile chosen when ties are broken with the positive nudge
+    pub fn primary(&self) -> HexCoord {
+        match self {
+            LineStep::Single(coord) => *coord,
+            LineStep::Tie(coord, _) => *coord,
+        }
+    }
+}
+
+/// Rounds a point in cube space to the nearest tile.
+/// Done in f64 so the nudge isn't lost far from the origin
+/// see: https://www.redblobgames.com/grids/hexagons/#rounding
+fn cube_round(q: f64, r: f64, s: f64) -> HexCoord {
+    let mut rq = q.round();
+    let mut rr = r.round();
+    let rs = s.round();
+    let dq = (rq - q).abs();
+    let dr = (rr - r).abs();
+    let ds = (rs - s).abs();
+    if dq > dr && dq > ds {
+        rq = -rr - rs;
+    } else if dr > ds {
+        rr = -rq - rs;
+    }
+    HexCoord(rq as i32, rr as i32)
+}
+
+/// Samples the line from start to end once per tile of distance,
+/// shifting both ends by `sign * NUDGE`
+fn sample(start: HexCoord, end: HexCoord, sign: f64) -> impl Iterator<Item = HexCoord> {
+    let dist = start.distance(end);
+    let nudge = |coord: HexCoord| (
+        coord.q() as f64 + sign * NUDGE.0,
+        coord.r() as f64 + sign * NUDGE.1,
+        coord.s() as f64 + sign * NUDGE.2,
+    );
+    let (aq, ar, as_) = nudge(start);
+    let (bq, br, bs) = nudge(end);
+    (0..=dist).map(move |point| {
+        // dist == 0 would divide by zero, but then the only point is the start
+        let t = if dist == 0 { 0. } else { point as f64 / dist as f64 };
+        cube_round(
+            aq + (bq - aq) * t,
+            ar + (br - ar) * t,
+            as_ + (bs - as_) * t,
+        )
+    })
+}
+
+/// Tiles along the straight line from start to end, including both ends.
+/// Always contains `distance + 1` tiles, each adjacent to the previous one
+pub fn line(start: HexCoord, end: HexCoord) -> Vec<HexCoord> {
+    let line: Vec<HexCoord> = sample(start, end, 1.).collect();
+    debug_assert!(line.windows(2).all(|pair| pair[0].distance(pair[1]) == 1));
+    line
+}
+
+/// Like `line` but reports both tiles wherever the line runs along an edge
+pub fn line_steps(start: HexCoord, end: HexCoord) -> Vec<LineStep> {
+    sample(start, end, 1.)
+        .zip(sample(start, end, -1.))
+        .map(|(positive, negative)| {
+            if positive == negative {
+                LineStep::Single(positive)
+            } else {
+                LineStep::Tie(positive, negative)
+            }
+        })
+        .collect()
+}
+
+/// Every tile the line touches, including both sides of any edge it runs along.
+/// Useful when anything blocking either side should block the line
+pub fn supercover(start: HexCoord, end: HexCoord) -> Vec<HexCoord> {
+    let mut tiles = Vec::new();
+    for step in line_steps(start, end) {
+        match step {
+            LineStep::Single(coord) => tiles.push(coord),
+            LineStep::Tie(first, second) => {
+                tiles.push(first);
+                tiles.push(second);
+            }
+        }
+    }
+    tiles
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Tests ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_are_contiguous() {
+        let start = HexCoord(-1, 2);
+        for end in start.spiral(6) {
+            let line = line(start, end);
+            assert_eq!(line.len() as u64, start.distance(end) + 1);
+            assert_eq!(line.first(), Some(&start));
+            assert_eq!(line.last(), Some(&end));
+            for pair in line.windows(2) {
+                assert_eq!(pair[0].distance(pair[1]), 1, "{:?} to {:?}", start, end);
+            }
+        }
+    }
+
+    #[test]
+    fn zero_length_line() {
+        let coord = HexCoord(3, -4);
+        assert_eq!(line(coord, coord), vec![coord]);
+        assert_eq!(line_steps(coord, coord), vec![LineStep::Single(coord)]);
+        assert_eq!(supercover(coord, coord), vec![coord]);
+    }
+
+    #[test]
+    fn edge_lines_tie() {
+        // the middle of this line runs between HexCoord(1, 0) and HexCoord(0, 1)
+        let steps = line_steps(HexCoord(0, 0), HexCoord(1, 1));
+        assert_eq!(steps[0], LineStep::Single(HexCoord(0, 0)));
+        assert_eq!(steps[2], LineStep::Single(HexCoord(1, 1)));
+        match steps[1] {
+            LineStep::Tie(first, second) => {
+                let mut tied = [first, second];
+                tied.sort_by_key(|coord| coord.0);
+                assert_eq!(tied, [HexCoord(0, 1), HexCoord(1, 0)]);
+            },
+            LineStep::Single(coord) => panic!("expected a tie, got {:?}", coord)
+        }
+        assert_eq!(supercover(HexCoord(0, 0), HexCoord(1, 1)).len(), 4);
+    }
+}
@@ -0,0 +1,134 @@
+//! Row and column addressing for rectangular maps.
+//! See: https://www.redblobgames.com/grids/hexagons/#coordinates-offset
+//! and: https://www.redblobgames.com/grids/hexagons/#coordinates-doubled
+use crate::hex::HexCoord;
+
+/// Which rows or columns get shoved over by half a tile.
+/// Row variants suit pointy layouts, column variants suit flat ones
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum OffsetKind {
+    OddR,
+    EvenR,
+    OddQ,
+    EvenQ
+}
+
+/// Tile position as column and row of a rectangular map
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct OffsetCoord {
+    pub col: i32,
+    pub row: i32
+}
+
+impl OffsetCoord {
+    pub fn new(col: i32, row: i32) -> Self {
+        Self { col, row }
+    }
+
+    pub fn from_hex(coord: HexCoord, kind: OffsetKind) -> Self {
+        let (q, r) = (coord.0, coord.1);
+        match kind {
+            OffsetKind::OddR => Self::new(q + (r - (r & 1)) / 2, r),
+            OffsetKind::EvenR => Self::new(q + (r + (r & 1)) / 2, r),
+            OffsetKind::OddQ => Self::new(q, r + (q - (q & 1)) / 2),
+            OffsetKind::EvenQ => Self::new(q, r + (q + (q & 1)) / 2),
+        }
+    }
+
+    pub fn to_hex(self, kind: OffsetKind) -> HexCoord {
+        let (col, row) = (self.col, self.row);
+        match kind {
+            OffsetKind::OddR => HexCoord(col - (row - (row & 1)) / 2, row),
+            OffsetKind::EvenR => HexCoord(col - (row + (row & 1)) / 2, row),
+            OffsetKind::OddQ => HexCoord(col, row - (col - (col & 1)) / 2),
+            OffsetKind::EvenQ => HexCoord(col, row - (col + (col & 1)) / 2),
+        }
+    }
+}
+
+/// Which axis is doubled so every tile lands on integer coordinates
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DoubledKind {
+    /// Columns step by two. Suits pointy layouts
+    Width,
+    /// Rows step by two. Suits flat layouts
+    Height
+}
+
+/// Tile position where col + row is always even
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DoubledCoord {
+    pub col: i32,
+    pub row: i32
+}
+
+impl DoubledCoord {
+    pub fn new(col: i32, row: i32) -> Self {
+        debug_assert!((col + row) % 2 == 0, "doubled coords must have an even sum");
+        Self { col, row }
+    }
+
+    pub fn from_hex(coord: HexCoord, kind: DoubledKind) -> Self {
+        let (q, r) = (coord.0, coord.1);
+        match kind {
+            DoubledKind::Width => Self::new(2 * q + r, r),
+            DoubledKind::Height => Self::new(q, 2 * r + q),
+        }
+    }
+
+    pub fn to_hex(self, kind: DoubledKind) -> HexCoord {
+        match kind {
+            DoubledKind::Width => HexCoord((self.col - self.row) / 2, self.row),
+            DoubledKind::Height => HexCoord(self.col, (self.row - self.col) / 2),
+        }
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Tests ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OFFSET_KINDS: [OffsetKind; 4] = [OffsetKind::OddR, OffsetKind::EvenR, OffsetKind::OddQ, OffsetKind::EvenQ];
+    const DOUBLED_KINDS: [DoubledKind; 2] = [DoubledKind::Width, DoubledKind::Height];
+
+    #[test]
+    fn offset_round_trip() {
+        for kind in OFFSET_KINDS {
+            // odd and even rows and columns on both sides of zero
+            for coord in HexCoord::ZERO.spiral(6) {
+                assert_eq!(OffsetCoord::from_hex(coord, kind).to_hex(kind), coord, "{:?}", kind);
+                assert_eq!(HexCoord::from_offset(coord.to_offset(kind), kind), coord, "{:?}", kind);
+            }
+            for col in -6..=6 {
+                for row in -6..=6 {
+                    let offset = OffsetCoord::new(col, row);
+                    assert_eq!(OffsetCoord::from_hex(offset.to_hex(kind), kind), offset, "{:?}", kind);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn offset_shoves_the_right_rows() {
+        assert_eq!(OffsetCoord::from_hex(HexCoord(0, -1), OffsetKind::OddR), OffsetCoord::new(-1, -1));
+        assert_eq!(OffsetCoord::from_hex(HexCoord(0, -1), OffsetKind::EvenR), OffsetCoord::new(0, -1));
+        assert_eq!(OffsetCoord::from_hex(HexCoord(1, 0), OffsetKind::OddQ), OffsetCoord::new(1, 0));
+        assert_eq!(OffsetCoord::from_hex(HexCoord(1, 0), OffsetKind::EvenQ), OffsetCoord::new(1, 1));
+    }
+
+    #[test]
+    fn doubled_round_trip() {
+        for kind in DOUBLED_KINDS {
+            for coord in HexCoord::ZERO.spiral(6) {
+                let doubled = DoubledCoord::from_hex(coord, kind);
+                assert_eq!((doubled.col + doubled.row).rem_euclid(2), 0);
+                assert_eq!(doubled.to_hex(kind), coord, "{:?}", kind);
+                assert_eq!(HexCoord::from_doubled(coord.to_doubled(kind), kind), coord, "{:?}", kind);
+            }
+        }
+        assert_eq!(DoubledCoord::from_hex(HexCoord(1, 1), DoubledKind::Width), DoubledCoord::new(3, 1));
+        assert_eq!(DoubledCoord::from_hex(HexCoord(1, 1), DoubledKind::Height), DoubledCoord::new(1, 3));
+    }
+}
@@ -0,0 +1,268 @@
+//! Cheapest paths between tiles.
+//! See: https://www.redblobgames.com/pathfinding/a-star/introduction.html
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::hex::{HexCoord, HexRegion};
+
+/// The rules for moving from tile to tile
+pub trait HexGraph {
+    /// Cost of stepping from `from` onto its neighbor `to`. None if the step isn't allowed
+    fn step_cost(&self, from: HexCoord, to: HexCoord) -> Option<u32>;
+
+    /// Tiles that can be stepped to from coord. Override to wrap around the map
+    fn neighbors(&self, coord: HexCoord) -> [HexCoord; 6] {
+        coord.neighbors()
+    }
+
+    /// Tiles between a and b ignoring costs. Override to wrap around the map
+    fn distance(&self, a: HexCoord, b: HexCoord) -> u64 {
+        a.distance(b)
+    }
+
+    /// The cheapest any single step can be. Guides the search toward the goal.
+    /// Must never be more than a real step costs or paths may not be the cheapest.
+    /// Zero is always safe but searches more tiles
+    fn min_step_cost(&self) -> u32 {
+        0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexPath {
+    /// Every tile stepped on, including the start and the goal
+    pub tiles: Vec<HexCoord>,
+    /// Sum of the cost of every step
+    pub cost: u32
+}
+
+impl HexPath {
+    pub fn start(&self) -> HexCoord {
+        self.tiles[0]
+    }
+
+    pub fn goal(&self) -> HexCoord {
+        self.tiles[self.tiles.len() - 1]
+    }
+
+    /// Number of steps taken. One less than the number of tiles
+    pub fn steps(&self) -> usize {
+        self.tiles.len() - 1
+    }
+}
+
+/// Walks back through `came_from` to rebuild the path ending at goal
+fn reconstruct(came_from: &HashMap<HexCoord, HexCoord>, goal: HexCoord) -> Vec<HexCoord> {
+    let mut tiles = vec![goal];
+    let mut current = goal;
+    while let Some(previous) = came_from.get(&current) {
+        tiles.push(*previous);
+        current = *previous;
+    }
+    tiles.reverse();
+    tiles
+}
+
+/// Cheapest path from start to goal using A*.
+/// None if the goal can't be reached.
+///
+/// The graph must only allow finitely many tiles or an unreachable goal is searched for forever
+pub fn find_path(graph: &impl HexGraph, start: HexCoord, goal: HexCoord) -> Option<HexPath> {
+    let heuristic = |coord: HexCoord| graph.distance(coord, goal) as u32 * graph.min_step_cost();
+
+    // ordered by estimated total cost, then by estimate remaining so ties lean toward the goal,
+    // then by coord so the same inputs always give the same path
+    let mut frontier = BinaryHeap::new();
+    let mut came_from: HashMap<HexCoord, HexCoord> = HashMap::new();
+    let mut cost_so_far: HashMap<HexCoord, u32> = HashMap::new();
+
+    frontier.push(Reverse((heuristic(start), heuristic(start), start)));
+    cost_so_far.insert(start, 0);
+
+    while let Some(Reverse((_, _, current))) = frontier.pop() {
+        let current_cost = cost_so_far[&current];
+        if current == goal {
+            return Some(HexPath { tiles: reconstruct(&came_from, goal), cost: current_cost });
+        }
+        for next in graph.neighbors(current) {
+            let step = match graph.step_cost(current, next) {
+                Some(step) => step,
+                None => continue
+            };
+            let new_cost = current_cost + step;
+            let improved = match cost_so_far.get(&next) {
+                Some(old_cost) => new_cost < *old_cost,
+                None => true
+            };
+            if improved {
+                cost_so_far.insert(next, new_cost);
+                came_from.insert(next, current);
+                let estimate = heuristic(next);
+                frontier.push(Reverse((new_cost + estimate, estimate, next)));
+            }
+        }
+    }
+    None
+}
+
+/// Every tile that can be reached from a start without spending more than a budget.
+/// Built by `reachable`
+#[derive(Debug, Clone)]
+pub struct ReachableArea {
+    start: HexCoord,
+    costs: HashMap<HexCoord, u32>,
+    came_from: HashMap<HexCoord, HexCoord>
+}
+
+impl ReachableArea {
+    pub fn start(&self) -> HexCoord {
+        self.start
+    }
+
+    pub fn contains(&self, coord: HexCoord) -> bool {
+        self.costs.contains_key(&coord)
+    }
+
+    /// Cheapest cost to get to coord. None if it is out of reach
+    pub fn cost_to(&self, coord: HexCoord) -> Option<u32> {
+        self.costs.get(&coord).copied()
+    }
+
+    /// Cheapest path from the start to coord. None if it is out of reach
+    pub fn path_to(&self, coord: HexCoord) -> Option<HexPath> {
+        let cost = self.cost_to(coord)?;
+        Some(HexPath { tiles: reconstruct(&self.came_from, coord), cost })
+    }
+
+    /// Reachable tiles, including the start
+    pub fn tiles(&self) -> impl Iterator<Item = HexCoord> + '_ {
+        self.costs.keys().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.costs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.costs.is_empty()
+    }
+
+    pub fn to_region(&self) -> HexRegion {
+        self.tiles().collect()
+    }
+}
+
+/// Every tile reachable from start spending at most budget, using Dijkstra's algorithm.
+/// See: https://www.redblobgames.com/pathfinding/a-star/introduction.html#dijkstra
+pub fn reachable(graph: &impl HexGraph, start: HexCoord, budget: u32) -> ReachableArea {
+    let mut frontier = BinaryHeap::new();
+    let mut came_from: HashMap<HexCoord, HexCoord> = HashMap::new();
+    let mut costs: HashMap<HexCoord, u32> = HashMap::new();
+
+    frontier.push(Reverse((0, start)));
+    costs.insert(start, 0);
+
+    while let Some(Reverse((cost, current))) = frontier.pop() {
+        // a cheaper way here was already found after this entry was queued
+        if cost > costs[&current] {
+            continue;
+        }
+        for next in graph.neighbors(current) {
+            let step = match graph.step_cost(current, next) {
+                Some(step) => step,
+                None => continue
+            };
+            let new_cost = cost + step;
+            if new_cost > budget {
+                continue;
+            }
+            let improved = match costs.get(&next) {
+                Some(old_cost) => new_cost < *old_cost,
+                None => true
+            };
+            if improved {
+                costs.insert(next, new_cost);
+                came_from.insert(next, current);
+                frontier.push(Reverse((new_cost, next)));
+            }
+        }
+    }
+    ReachableArea { start, costs, came_from }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Tests ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hexagon of radius 6 with uneven costs and a wall closed around HexCoord(4, -2)
+    struct TestGraph {
+        walls: HexRegion
+    }
+
+    impl TestGraph {
+        fn new() -> Self {
+            Self { walls: HexCoord(4, -2).ring(1).collect() }
+        }
+
+        fn tile_cost(coord: HexCoord) -> u32 {
+            1 + (coord.0 * 7 + coord.1 * 13).rem_euclid(5) as u32
+        }
+    }
+
+    impl HexGraph for TestGraph {
+        fn step_cost(&self, _from: HexCoord, to: HexCoord) -> Option<u32> {
+            if to.length() > 6 || self.walls.contains(to) {
+                return None;
+            }
+            Some(Self::tile_cost(to))
+        }
+
+        fn min_step_cost(&self) -> u32 {
+            1
+        }
+    }
+
+    #[test]
+    fn a_star_matches_dijkstra() {
+        let graph = TestGraph::new();
+        let start = HexCoord(-3, 1);
+        let area = reachable(&graph, start, u32::MAX);
+        for goal in HexCoord::ZERO.spiral(6) {
+            let path = find_path(&graph, start, goal);
+            assert_eq!(path.as_ref().map(|path| path.cost), area.cost_to(goal), "to {:?}", goal);
+            if let Some(path) = path {
+                assert_eq!(path.start(), start);
+                assert_eq!(path.goal(), goal);
+                let mut cost = 0;
+                for pair in path.tiles.windows(2) {
+                    assert_eq!(pair[0].distance(pair[1]), 1);
+                    cost += graph.step_cost(pair[0], pair[1]).unwrap();
+                }
+                assert_eq!(cost, path.cost);
+            }
+        }
+    }
+
+    #[test]
+    fn unreachable_goal() {
+        let graph = TestGraph::new();
+        let start = HexCoord(-3, 1);
+        // walled in
+        assert_eq!(find_path(&graph, start, HexCoord(4, -2)), None);
+        // off the map
+        assert_eq!(find_path(&graph, start, HexCoord(9, 0)), None);
+        let area = reachable(&graph, start, u32::MAX);
+        assert!(!area.contains(HexCoord(4, -2)));
+        assert_eq!(area.path_to(HexCoord(9, 0)), None);
+    }
+
+    #[test]
+    fn start_is_goal() {
+        let graph = TestGraph::new();
+        let path = find_path(&graph, HexCoord(1, 1), HexCoord(1, 1)).unwrap();
+        assert_eq!(path.tiles, vec![HexCoord(1, 1)]);
+        assert_eq!(path.cost, 0);
+    }
+}
@@ -0,0 +1,260 @@
+//! Sets of tiles
+use std::collections::{HashSet, VecDeque};
+
+use crate::hex::{HexCoord, HexDirection};
+
+/// An unordered set of tiles.
+///
+/// Iteration order is not stable between runs.
+/// Sort the tiles first if the order matters
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HexRegion {
+    tiles: HashSet<HexCoord>
+}
+
+impl HexRegion {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every tile within radius of center
+    pub fn hexagon(center: HexCoord, radius: i32) -> Self {
+        center.within_radius(radius).collect()
+    }
+
+    /// Starting at start, grows outward through neighbors for as long as `passable` is true.
+    /// Start is only included if it passes too.
+    ///
+    /// `passable` must reject all but finitely many tiles or this never returns
+    pub fn flood_fill(start: HexCoord, mut passable: impl FnMut(HexCoord) -> bool) -> Self {
+        let mut region = Self::new();
+        if !passable(start) {
+            return region;
+        }
+        let mut frontier = VecDeque::from([start]);
+        region.insert(start);
+        while let Some(coord) = frontier.pop_front() {
+            for neighbor in coord.neighbors() {
+                if !region.contains(neighbor) && passable(neighbor) {
+                    region.insert(neighbor);
+                    frontier.push_back(neighbor);
+                }
+            }
+        }
+        region
+    }
+
+    pub fn insert(&mut self, coord: HexCoord) -> bool {
+        self.tiles.insert(coord)
+    }
+
+    pub fn remove(&mut self, coord: HexCoord) -> bool {
+        self.tiles.remove(&coord)
+    }
+
+    pub fn contains(&self, coord: HexCoord) -> bool {
+        self.tiles.contains(&coord)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = HexCoord> + '_ {
+        self.tiles.iter().copied()
+    }
+
+    /// Tiles sorted by q then r, for when a stable order is needed
+    pub fn sorted(&self) -> Vec<HexCoord> {
+        let mut tiles: Vec<HexCoord> = self.iter().collect();
+        tiles.sort();
+        tiles
+    }
+
+    // ~~~~~~~~~~~~~~ Set Operations ~~~~~~~~~~~~~~ //
+
+    pub fn union(&self, other: &HexRegion) -> HexRegion {
+        self.tiles.union(&other.tiles).copied().collect()
+    }
+
+    pub fn intersection(&self, other: &HexRegion) -> HexRegion {
+        self.tiles.intersection(&other.tiles).copied().collect()
+    }
+
+    pub fn difference(&self, other: &HexRegion) -> HexRegion {
+        self.tiles.difference(&other.tiles).copied().collect()
+    }
+
+    pub fn symmetric_difference(&self, other: &HexRegion) -> HexRegion {
+        self.tiles.symmetric_difference(&other.tiles).copied().collect()
+    }
+
+    pub fn is_subset(&self, other: &HexRegion) -> bool {
+        self.tiles.is_subset(&other.tiles)
+    }
+
+    // ~~~~~~~~~~~~~~~~~ Shape ~~~~~~~~~~~~~~~~~ //
+
+    /// Tiles outside the region that touch it.
+    /// Holes inside the region are left out
+    pub fn outer_boundary(&self) -> HexRegion {
+        let center = match self.iter().next() {
+            Some(coord) => coord,
+            None => return HexRegion::new()
+        };
+        // flood the outside from a tile past the region. Holes can't be reached from there
+        let radius = self.bounding_radius(center).unwrap_or(0) + 1;
+        let start = (0..radius).fold(center, |coord, _| coord.neighbor(HexDirection::ALL[0]));
+        let outside = HexRegion::flood_fill(start, |tile| {
+            !self.contains(tile) && tile.distance(center) <= radius
+        });
+        self.iter()
+            .flat_map(|coord| coord.neighbors())
+            .filter(|neighbor| outside.contains(*neighbor))
+            .collect()
+    }
+
+    /// Tiles inside the region that touch a tile outside of it
+    pub fn inner_boundary(&self) -> HexRegion {
+        self.iter()
+            .filter(|coord| coord.neighbors().iter().any(|neighbor| !self.contains(*neighbor)))
+            .collect()
+    }
+
+    /// Every edge between a tile in the region and one outside it,
+    /// as the inside tile and the direction facing out through the edge
+    pub fn perimeter_edges(&self) -> Vec<(HexCoord, HexDirection)> {
+        let mut edges = Vec::new();
+        for coord in self.sorted() {
+            for direction in HexDirection::ALL {
+                if !self.contains(coord.neighbor(direction)) {
+                    edges.push((coord, direction));
+                }
+            }
+        }
+        edges
+    }
+
+    /// Splits the region into groups of tiles that are connected through neighbors.
+    /// Groups are ordered by their smallest tile so labels are stable between runs
+    pub fn connected_components(&self) -> Vec<HexRegion> {
+        let mut unvisited = self.clone();
+        let mut components = Vec::new();
+        for coord in self.sorted() {
+            if !unvisited.contains(coord) {
+                continue;
+            }
+            let component = HexRegion::flood_fill(coord, |tile| unvisited.contains(tile));
+            for tile in component.iter() {
+                unvisited.remove(tile);
+            }
+            components.push(component);
+        }
+        components
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected_components().len() <= 1
+    }
+
+    /// Smallest radius around center that contains every tile in the region.
+    /// None if the region is empty
+    pub fn bounding_radius(&self, center: HexCoord) -> Option<u64> {
+        self.iter().map(|coord| coord.distance(center)).max()
+    }
+}
+
+impl FromIterator<HexCoord> for HexRegion {
+    fn from_iter<T: IntoIterator<Item = HexCoord>>(iter: T) -> Self {
+        Self { tiles: iter.into_iter().collect() }
+    }
+}
+
+impl Extend<HexCoord> for HexRegion {
+    fn extend<T: IntoIterator<Item = HexCoord>>(&mut self, iter: T) {
+        self.tiles.extend(iter)
+    }
+}
+
+impl IntoIterator for HexRegion {
+    type Item = HexCoord;
+    type IntoIter = std::collections::hash_set::IntoIter<HexCoord>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tiles.into_iter()
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Tests ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flood_fill_stops_at_walls() {
+        let origin = HexCoord(0, 0);
+        let wall: HexRegion = origin.ring(2).collect();
+        let inside = HexRegion::flood_fill(origin, |tile| !wall.contains(tile));
+        assert_eq!(inside, HexRegion::hexagon(origin, 1));
+
+        let bounded = HexRegion::flood_fill(HexCoord(1, -1), |tile| tile.distance(origin) <= 2);
+        assert_eq!(bounded, HexRegion::hexagon(origin, 2));
+
+        // start is blocked so nothing gets filled
+        assert!(HexRegion::flood_fill(origin, |tile| tile != origin).is_empty());
+    }
+
+    #[test]
+    fn disjoint_blobs_are_separate_components() {
+        let left = HexRegion::hexagon(HexCoord(0, 0), 1);
+        let right = HexRegion::hexagon(HexCoord(5, 0), 1);
+        let both = left.union(&right);
+        assert_eq!(both.connected_components(), vec![left.clone(), right]);
+        assert!(!both.is_connected());
+        assert!(left.is_connected());
+        assert!(HexRegion::new().connected_components().is_empty());
+        assert!(HexRegion::new().is_connected());
+    }
+
+    #[test]
+    fn hexagon_perimeter() {
+        for radius in 0..6 {
+            let hexagon = HexRegion::hexagon(HexCoord(2, -3), radius);
+            let edges = hexagon.perimeter_edges();
+            assert_eq!(edges.len(), 6 * (2 * radius as usize + 1), "radius {}", radius);
+            for (coord, direction) in edges {
+                assert!(hexagon.contains(coord));
+                assert!(!hexagon.contains(coord.neighbor(direction)));
+            }
+            let ring: HexRegion = HexCoord(2, -3).ring(radius + 1).collect();
+            assert_eq!(hexagon.outer_boundary(), ring);
+        }
+    }
+
+    #[test]
+    fn outer_boundary_skips_holes() {
+        let origin = HexCoord(0, 0);
+        let mut donut = HexRegion::hexagon(origin, 2);
+        donut.remove(origin);
+        let outer = donut.outer_boundary();
+        assert!(!outer.contains(origin));
+        assert_eq!(outer, origin.ring(3).collect());
+        // the tiles around the hole are still on the inside edge
+        assert_eq!(donut.inner_boundary().len(), 6 + 12);
+        assert!(HexRegion::new().outer_boundary().is_empty());
+    }
+
+    #[test]
+    fn bounding_radius() {
+        let center = HexCoord(2, -1);
+        let hexagon = HexRegion::hexagon(center, 3);
+        assert_eq!(hexagon.bounding_radius(center), Some(3));
+        assert_eq!(hexagon.bounding_radius(HexCoord(3, -1)), Some(4));
+        assert_eq!(HexRegion::new().bounding_radius(center), None);
+    }
+}
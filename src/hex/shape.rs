@@ -0,0 +1,203 @@
+//! Outlines of boards that can be spawned
+use bevy::prelude::*;
+
+use crate::hex::{HexCoord, HexRegion};
+use crate::hex::offset::{OffsetCoord, OffsetKind};
+
+/// Which tiles make up the board.
+/// Insert as a resource before the grid spawns to pick the board
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub enum HexShape {
+    /// Every tile within radius of HexCoord(0,0)
+    Hexagon { radius: i32 },
+    /// width columns by height rows, with OffsetCoord(0,0) in the corner
+    Rectangle { width: i32, height: i32, offset: OffsetKind },
+    /// width steps along q by height steps along r, starting at HexCoord(0,0)
+    Parallelogram { width: i32, height: i32 },
+    /// Triangle with `size` tiles along each side and a corner on HexCoord(0,0)
+    Triangle { size: i32 },
+    /// Any set of tiles
+    Mask(HexRegion)
+}
+
+impl HexShape {
+    /// Reads a mask drawn as text. Each line is a row and each character a column,
+    /// addressed with `offset`. '.' and ' ' are empty, anything else is a tile.
+    ///
+    /// ```text
+    /// .####.
+    /// ######
+    /// .####.
+    /// ```
+    pub fn mask_from_str(data: &str, offset: OffsetKind) -> Self {
+        let mut region = HexRegion::new();
+        for (row, line) in data.lines().enumerate() {
+            for (col, tile) in line.chars().enumerate() {
+                if tile != '.' && tile != ' ' {
+                    region.insert(OffsetCoord::new(col as i32, row as i32).to_hex(offset));
+                }
+            }
+        }
+        HexShape::Mask(region)
+    }
+
+    /// Same as `mask_from_str` but reads the text from a file
+    pub fn mask_from_file(path: impl AsRef<std::path::Path>, offset: OffsetKind) -> std::io::Result<Self> {
+        let data = std::fs::read_to_string(path)?;
+        Ok(Self::mask_from_str(&data, offset))
+    }
+
+    /// All tiles in the shape in a stable order.
+    /// Hexagons come out from the center outward, everything else row by row
+    pub fn tiles(&self) -> Vec<HexCoord> {
+        match self {
+            HexShape::Hexagon { radius } => HexCoord(0,0).spiral(*radius).collect(),
+            HexShape::Rectangle { width, height, offset } => {
+                let mut tiles = Vec::new();
+                for row in 0..*height {
+                    for col in 0..*width {
+                        tiles.push(OffsetCoord::new(col, row).to_hex(*offset));
+                    }
+                }
+                tiles
+            },
+            HexShape::Parallelogram { width, height } => {
+                let mut tiles = Vec::new();
+                for r in 0..*height {
+                    for q in 0..*width {
+                        tiles.push(HexCoord(q, r));
+                    }
+                }
+                tiles
+            },
+            HexShape::Triangle { size } => {
+                let mut tiles = Vec::new();
+                for r in 0..*size {
+                    for q in 0..(size - r) {
+                        tiles.push(HexCoord(q, r));
+                    }
+                }
+                tiles
+            },
+            HexShape::Mask(region) => {
+                let mut tiles: Vec<HexCoord> = region.iter().collect();
+                tiles.sort_by_key(|coord| (coord.1, coord.0));
+                tiles
+            },
+        }
+    }
+
+    pub fn contains(&self, coord: HexCoord) -> bool {
+        match self {
+            HexShape::Hexagon { radius } => coord.length() <= *radius as u64,
+            HexShape::Rectangle { width, height, offset } => {
+                let OffsetCoord { col, row } = OffsetCoord::from_hex(coord, *offset);
+                (0..*width).contains(&col) && (0..*height).contains(&row)
+            },
+            HexShape::Parallelogram { width, height } => {
+                (0..*width).contains(&coord.0) && (0..*height).contains(&coord.1)
+            },
+            HexShape::Triangle { size } => {
+                coord.0 >= 0 && coord.1 >= 0 && coord.0 + coord.1 < *size
+            },
+            HexShape::Mask(region) => region.contains(coord),
+        }
+    }
+
+    pub fn to_region(&self) -> HexRegion {
+        match self {
+            HexShape::Mask(region) => region.clone(),
+            _ => self.tiles().into_iter().collect(),
+        }
+    }
+
+    /// The tile in the shape closest to its middle. Good place to start the camera or a piece.
+    /// None if the shape is empty
+    pub fn center(&self) -> Option<HexCoord> {
+        let tiles = self.tiles();
+        if tiles.is_empty() {
+            return None;
+        }
+        let sum = tiles.iter().fold(Vec2::ZERO, |sum, coord| sum + Vec2::new(coord.0 as f32, coord.1 as f32));
+        let average = sum / tiles.len() as f32;
+        let middle = HexCoord::from_floating((average.x, average.y));
+        tiles.into_iter().min_by_key(|coord| coord.distance(middle))
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Tests ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MASK: &str = ".####.\n######\n.####.";
+
+    fn shapes() -> Vec<HexShape> {
+        let mut shapes = vec![
+            HexShape::Hexagon { radius: 0 },
+            HexShape::Hexagon { radius: 4 },
+            HexShape::Parallelogram { width: 5, height: 3 },
+            HexShape::Triangle { size: 1 },
+            HexShape::Triangle { size: 6 },
+            HexShape::Mask(HexRegion::new()),
+        ];
+        for offset in [OffsetKind::OddR, OffsetKind::EvenR, OffsetKind::OddQ, OffsetKind::EvenQ] {
+            shapes.push(HexShape::Rectangle { width: 7, height: 4, offset });
+            shapes.push(HexShape::mask_from_str(MASK, offset));
+        }
+        shapes
+    }
+
+    #[test]
+    fn tiles_and_contains_agree() {
+        for shape in shapes() {
+            let tiles = shape.tiles();
+            let region: HexRegion = tiles.iter().copied().collect();
+            assert_eq!(region.len(), tiles.len(), "{:?} has repeated tiles", shape);
+            assert_eq!(shape.to_region(), region);
+            for coord in HexCoord(0, 0).spiral(12) {
+                assert_eq!(shape.contains(coord), region.contains(coord), "{:?} at {:?}", shape, coord);
+            }
+        }
+    }
+
+    #[test]
+    fn tile_counts() {
+        for n in 0..6 {
+            assert_eq!(HexShape::Hexagon { radius: n }.tiles().len() as i32, 3 * n * (n + 1) + 1);
+            assert_eq!(HexShape::Triangle { size: n }.tiles().len() as i32, n * (n + 1) / 2);
+            assert_eq!(HexShape::Parallelogram { width: n, height: 3 }.tiles().len() as i32, n * 3);
+            let rectangle = HexShape::Rectangle { width: n, height: n + 2, offset: OffsetKind::OddR };
+            assert_eq!(rectangle.tiles().len() as i32, n * (n + 2));
+        }
+    }
+
+    #[test]
+    fn mask_reads_rows_and_columns() {
+        let offset = OffsetKind::OddR;
+        let shape = HexShape::mask_from_str(MASK, offset);
+        assert_eq!(shape.tiles().len(), 4 + 6 + 4);
+        assert!(!shape.contains(OffsetCoord::new(0, 0).to_hex(offset)));
+        assert!(shape.contains(OffsetCoord::new(1, 0).to_hex(offset)));
+        assert!(shape.contains(OffsetCoord::new(0, 1).to_hex(offset)));
+        assert!(!shape.contains(OffsetCoord::new(5, 2).to_hex(offset)));
+        // spaces are empty too
+        assert_eq!(HexShape::mask_from_str(" # \n", offset).tiles(), vec![OffsetCoord::new(1, 0).to_hex(offset)]);
+    }
+
+    #[test]
+    fn center() {
+        assert_eq!(HexShape::Hexagon { radius: 5 }.center(), Some(HexCoord(0, 0)));
+        assert_eq!(HexShape::Parallelogram { width: 3, height: 3 }.center(), Some(HexCoord(1, 1)));
+        assert_eq!(HexShape::Triangle { size: 4 }.center(), Some(HexCoord(1, 1)));
+        let single = HexShape::Mask(HexRegion::from_iter([HexCoord(7, -2)]));
+        assert_eq!(single.center(), Some(HexCoord(7, -2)));
+        assert_eq!(HexShape::Mask(HexRegion::new()).center(), None);
+        for shape in shapes() {
+            if let Some(center) = shape.center() {
+                assert!(shape.contains(center), "{:?} centered off the board", shape);
+            }
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! Maps with no edges.
+//!
+//! A wrapping map is a rectangle of odd-r offset rows (see `OffsetKind::OddR`)
+//! where walking off one side brings you back on the other.
+//! Every tile has infinitely many copies tiled across hex space,
+//! one per period of the map. `canonical` picks the copy inside the rectangle.
+use bevy::prelude::*;
+
+use crate::hex::{HexCoord, HexDirection, HexShape, OffsetCoord, OffsetKind};
+
+#[derive(Resource, Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum HexWrap {
+    /// Edges are edges
+    #[default]
+    None,
+    /// Columns wrap around so the map joins left to right
+    Cylinder { width: i32, height: i32 },
+    /// Columns and rows both wrap around. Height must be even so row parity lines up
+    Torus { width: i32, height: i32 }
+}
+
+impl HexWrap {
+    pub fn cylinder(width: i32, height: i32) -> Self {
+        assert!(width > 0 && height > 0, "wrapping maps need a positive size");
+        HexWrap::Cylinder { width, height }
+    }
+
+    pub fn torus(width: i32, height: i32) -> Self {
+        assert!(width > 0 && height > 0, "wrapping maps need a positive size");
+        assert!(height % 2 == 0, "torus maps need an even height to wrap odd-r rows");
+        HexWrap::Torus { width, height }
+    }
+
+    /// The board to spawn for this map. None for non wrapping maps as any shape will do
+    pub fn shape(&self) -> Option<HexShape> {
+        match *self {
+            HexWrap::None => None,
+            HexWrap::Cylinder { width, height } | HexWrap::Torus { width, height } => {
+                Some(HexShape::Rectangle { width, height, offset: OffsetKind::OddR })
+            }
+        }
+    }
+
+    /// Offsets in hex space between neighboring copies of the map.
+    /// Moving a tile by any whole number of these lands on the same tile
+    pub fn periods(&self) -> (Option<HexCoord>, Option<HexCoord>) {
+        match *self {
+            HexWrap::None => (None, None),
+            HexWrap::Cylinder { width, .. } => (Some(HexCoord(width, 0)), None),
+            // moving down an even number of odd-r rows shifts q back by half as much
+            HexWrap::Torus { width, height } => (Some(HexCoord(width, 0)), Some(HexCoord(-height / 2, height))),
+        }
+    }
+
+    /// The copy of coord that lies inside the map rectangle
+    pub fn canonical(&self, coord: HexCoord) -> HexCoord {
+        match *self {
+            HexWrap::None => coord,
+            HexWrap::Cylinder { width, .. } => {
+                let offset = coord.to_offset(OffsetKind::OddR);
+                OffsetCoord::new(offset.col.rem_euclid(width), offset.row).to_hex(OffsetKind::OddR)
+            },
+            HexWrap::Torus { width, height } => {
+                let offset = coord.to_offset(OffsetKind::OddR);
+                OffsetCoord::new(offset.col.rem_euclid(width), offset.row.rem_euclid(height)).to_hex(OffsetKind::OddR)
+            },
+        }
+    }
+
+    /// The copy of `to` closest to `from`.
+    /// Walking straight from `from` to the result takes the short way around the map
+    pub fn nearest_image(&self, from: HexCoord, to: HexCoord) -> HexCoord {
+        let (a, b) = self.periods();
+        let a = a.unwrap_or(HexCoord(0,0));
+        let b = b.unwrap_or(HexCoord(0,0));
+        // work from the canonical copy of from then shift back to wherever from actually is
+        let from_canonical = self.canonical(from);
+        let shift = from - from_canonical;
+        let to_canonical = self.canonical(to);
+        let mut nearest = to_canonical;
+        for i in -1..=1 {
+            for j in -1..=1 {
+                let image = to_canonical + a * i + b * j;
+                if image.distance(from_canonical) < nearest.distance(from_canonical) {
+                    nearest = image;
+                }
+            }
+        }
+        nearest + shift
+    }
+
+    /// Tiles between a and b going the short way around the map
+    pub fn distance(&self, a: HexCoord, b: HexCoord) -> u64 {
+        a.distance(self.nearest_image(a, b))
+    }
+
+    pub fn neighbor(&self, coord: HexCoord, direction: HexDirection) -> HexCoord {
+        self.canonical(coord.neighbor(direction))
+    }
+
+    /// Neighbors in the same order as `HexDirection::ALL`, wrapped onto the map
+    pub fn neighbors(&self, coord: HexCoord) -> [HexCoord; 6] {
+        coord.neighbors().map(|neighbor| self.canonical(neighbor))
+    }
+
+    /// Turns a path of wrapped tiles into one that never jumps across the seam,
+    /// starting from whichever copy of the first tile `start` is
+    pub fn unwrap_path(&self, start: HexCoord, path: &[HexCoord]) -> Vec<HexCoord> {
+        let mut previous = start;
+        path.iter()
+            .map(|coord| {
+                previous = self.nearest_image(previous, *coord);
+                previous
+            })
+            .collect()
+    }
+
+    /// Straight line from a to b the short way around the map, wrapped onto the map
+    pub fn line_between(&self, a: HexCoord, b: HexCoord) -> Vec<HexCoord> {
+        a.line_between(self.nearest_image(a, b))
+            .into_iter()
+            .map(|coord| self.canonical(coord))
+            .collect()
+    }
+
+    /// Every tile within radius of center, wrapped onto the map.
+    /// Ordered from the center outward. Once the radius reaches around the map
+    /// tiles are only listed the first time they are reached
+    pub fn within_radius(&self, center: HexCoord, radius: i32) -> Vec<HexCoord> {
+        let mut seen = std::collections::HashSet::new();
+        center
+            .spiral(radius)
+            .map(|coord| self.canonical(coord))
+            .filter(|coord| seen.insert(*coord))
+            .collect()
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Tests ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wraps() -> [HexWrap; 2] {
+        [HexWrap::cylinder(10, 6), HexWrap::torus(10, 6)]
+    }
+
+    /// Shortest distance to any copy of b, found by checking every nearby copy
+    fn brute_distance(wrap: &HexWrap, a: HexCoord, b: HexCoord) -> u64 {
+        let (pa, pb) = wrap.periods();
+        let pa = pa.unwrap_or(HexCoord(0,0));
+        let pb = pb.unwrap_or(HexCoord(0,0));
+        let mut best = u64::MAX;
+        for i in -3..=3 {
+            for j in -3..=3 {
+                best = best.min(a.distance(b + pa * i + pb * j));
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn canonical_is_idempotent() {
+        for wrap in wraps().into_iter().chain([HexWrap::None]) {
+            for coord in HexCoord::ZERO.spiral(25) {
+                let canonical = wrap.canonical(coord);
+                assert_eq!(wrap.canonical(canonical), canonical, "{:?}", wrap);
+                // only the wrapped axes are pulled into the rectangle
+                let offset = canonical.to_offset(OffsetKind::OddR);
+                match wrap {
+                    HexWrap::None => assert_eq!(canonical, coord),
+                    HexWrap::Cylinder { width, .. } => assert!((0..width).contains(&offset.col)),
+                    HexWrap::Torus { width, height } => {
+                        assert!(wrap.shape().unwrap().contains(canonical));
+                        assert!((0..width).contains(&offset.col) && (0..height).contains(&offset.row));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn periods_land_on_the_same_tile() {
+        for wrap in wraps() {
+            let (a, b) = wrap.periods();
+            for coord in HexCoord::ZERO.spiral(8) {
+                for period in [a, b].into_iter().flatten() {
+                    assert_eq!(wrap.canonical(coord + period), wrap.canonical(coord), "{:?}", wrap);
+                    assert_eq!(wrap.canonical(coord - period * 2), wrap.canonical(coord), "{:?}", wrap);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn nearest_image_crosses_the_seam() {
+        // last column of the first row is next to the first column
+        let cylinder = HexWrap::cylinder(10, 6);
+        assert_eq!(cylinder.nearest_image(HexCoord(9, 0), HexCoord(0, 0)), HexCoord(10, 0));
+        assert_eq!(cylinder.distance(HexCoord(9, 0), HexCoord(0, 0)), 1);
+        assert_eq!(cylinder.distance(HexCoord(0, 0), HexCoord(9, 0)), 1);
+        // rows don't wrap on a cylinder
+        assert_eq!(cylinder.nearest_image(HexCoord(0, 0), HexCoord(-2, 5)), HexCoord(-2, 5));
+
+        // the last row sits just above the first on a torus
+        let torus = HexWrap::torus(10, 6);
+        assert_eq!(torus.nearest_image(HexCoord(-2, 5), HexCoord(0, 0)), HexCoord(-3, 6));
+        assert_eq!(torus.distance(HexCoord(-2, 5), HexCoord(0, 0)), 1);
+        // and the last tile of the map touches the first across both seams
+        assert_eq!(torus.nearest_image(HexCoord(7, 5), HexCoord(0, 0)), HexCoord(7, 6));
+        assert_eq!(torus.distance(HexCoord(7, 5), HexCoord(0, 0)), 1);
+    }
+
+    #[test]
+    fn distance_takes_the_short_way() {
+        for wrap in wraps() {
+            let tiles = wrap.shape().unwrap().tiles();
+            for a in tiles.iter() {
+                for b in tiles.iter() {
+                    let distance = wrap.distance(*a, *b);
+                    assert_eq!(distance, brute_distance(&wrap, *a, *b), "{:?} {:?} to {:?}", wrap, a, b);
+                    assert_eq!(distance, wrap.distance(*b, *a), "{:?}", wrap);
+                }
+            }
+        }
+    }
+}
@@ -1 +1,2 @@
+pub mod hex;
 pub mod plugins;
\ No newline at end of file
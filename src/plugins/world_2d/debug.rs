@@ -1,7 +1,8 @@
 use bevy::prelude::*;
 use bevy_inspector_egui::{RegisterInspectable, WorldInspectorPlugin};
 
-use crate::plugins::world_2d::hex::{HexTile, HexCoord};
+use crate::hex::HexCoord;
+use crate::plugins::world_2d::hex::HexTile;
 
 pub struct DebugPlugin;
 
@@ -1,8 +1,7 @@
 use bevy::prelude::*;
 use bevy_inspector_egui::Inspectable;
-use std::cmp::{max,min};
 
-use crate::plugins::world_2d::WorldCoord;
+use crate::hex::{HexCoord, HexLayout, HexOrientation, HexShape, WorldPlane};
 use crate::plugins::world_2d::config::{HEX_CIRCUMRADIUS, HEX_GRID_RADIUS, HEX_SPRITE_SCALE};
 use crate::plugins::world_2d::mouse::MousePos;
 
@@ -11,66 +10,12 @@ pub struct HexPlugin;
 impl Plugin for HexPlugin {
     fn build(&self, app: &mut App) {
         app
+        .insert_resource(HexLayout::new(HexOrientation::Pointy, HEX_CIRCUMRADIUS, WorldPlane::XY))
+        .insert_resource(HexShape::Hexagon { radius: HEX_GRID_RADIUS })
         .add_startup_system_to_stage(StartupStage::PreStartup, HexGrid::spawn)
         .add_startup_system(init_highlighted)
-        .add_system(highlight_on_click);
-    }
-}
-
-#[derive(Component, Inspectable, Debug, Copy, Clone)]
-pub struct HexCoord(pub i32, pub i32);
-
-impl HexCoord {
-
-    pub fn from_world(world_coord: WorldCoord) -> HexCoord {
-        let x = (f32::sqrt(3.0)*world_coord.0 - world_coord.1) / 3.0 / HEX_CIRCUMRADIUS;
-        let y = ((2.0/3.0) * world_coord.1) / HEX_CIRCUMRADIUS;
-        HexCoord::from_floating((x,y))
-    }
-
-    /*
-    def axial_round(x, y):
-    xgrid = round(x); ygrid = round(y)
-    x -= xgrid; y -= ygrid # remainder
-    if abs(x) >= abs(y):
-        return [xgrid + round(x + 0.5*y), ygrid]
-    else:
-        return [xgrid, ygrid + round(y + 0.5*x)] */
-    pub fn from_floating((fx, fy): (f32, f32)) -> HexCoord {
-        let mut x = fx.round();
-        let mut y = fy.round();
-        let rem_x = fx - x;
-        let rem_y = fy - y;
-        if rem_x.abs() >= rem_y.abs() {
-            x += (rem_x + 0.5*rem_y).round();
-        } else {
-            y += (rem_y + 0.5*rem_x).round();
-        }
-        HexCoord(x as i32, y as i32)
-    }
-
-    pub fn to_world(&self) -> WorldCoord {
-        let x = HEX_CIRCUMRADIUS * f32::sqrt(3.0) * ((self.0 as f32) + (self.1 as f32) / 2.0);
-        let y = HEX_CIRCUMRADIUS * (3.0/2.0) * (self.1 as f32);
-        return (x,y);
-    }
-
-    // returns all the hex coords that are
-    // within radius number of tiles
-    pub fn within_radius(&self, radius: i32) -> Vec<HexCoord> {
-        let mut within = Vec::new();
-        for x in -radius..radius+1 {
-            for y in max(-radius, (-x)-radius)..min(radius,(-x)+radius)+1 {
-                within.push(HexCoord(x+self.0, y+self.1));
-            }
-        }
-        return within;
-    }
-}
-
-impl PartialEq for HexCoord {
-    fn eq(&self, other: &Self) -> bool {
-        self.0 == other.0 && self.1 == other.1
+        .add_system(highlight_on_click)
+        .add_system(relayout_tiles);
     }
 }
 
@@ -80,11 +25,13 @@ pub struct HexGrid;
  impl HexGrid {
     fn spawn(
         mut commands: Commands,
-        assets: Res<AssetServer>
+        assets: Res<AssetServer>,
+        layout: Res<HexLayout>,
+        shape: Res<HexShape>
     ) {
         let mut tiles = Vec::new();
-        for hex_coord in HexCoord(0,0).within_radius(HEX_GRID_RADIUS).into_iter() {
-            let tile = HexTile::spawn_at(hex_coord, &mut commands, &assets);
+        for hex_coord in shape.tiles() {
+            let tile = HexTile::spawn_at(hex_coord, &layout, &mut commands, &assets);
             tiles.push(tile);
         }
         commands
@@ -102,14 +49,13 @@ pub struct HexTile;
 impl HexTile {
     fn spawn_at(
         hex_coord: HexCoord,
+        layout: &HexLayout,
         commands: &mut Commands,
         assets: &Res<AssetServer>
     ) -> Entity {
-        let (x, y) = hex_coord.to_world();
         commands.spawn(SpriteBundle {
             texture: assets.load("textures/sprites/hex.png"),
-            transform: Transform::from_xyz(x, y, 0.0)
-                        .with_scale(Vec3::new(HEX_SPRITE_SCALE, HEX_SPRITE_SCALE, 1.0)),
+            transform: Self::transform(hex_coord, layout, 0.0),
             ..default()
         })
         .insert(Name::new("HexTile"))
@@ -117,28 +63,67 @@ impl HexTile {
         .insert(HexTile)
         .id()
     }
+
+    /// Where the sprite for hex_coord sits at depth z, sized and turned to match layout
+    fn transform(hex_coord: HexCoord, layout: &HexLayout, z: f32) -> Transform {
+        let Vec2 { x, y } = layout.to_pixel(hex_coord);
+        // the sprites are drawn pointy topped at HEX_CIRCUMRADIUS so stretch and turn them to the layout
+        let scale = HEX_SPRITE_SCALE * layout.size / HEX_CIRCUMRADIUS;
+        Transform::from_xyz(x, y, z)
+            .with_rotation(Quat::from_rotation_z(layout.orientation.tile_angle()))
+            .with_scale(Vec3::new(scale, scale, 1.0))
+    }
+}
+
+/// Moves and resizes the tiles when the layout changes
+fn relayout_tiles(
+    layout: Res<HexLayout>,
+    highlighted: Option<Res<Highlighted>>,
+    mut tiles: Query<(&HexCoord, &mut Transform), With<HexTile>>,
+    mut highlight: Query<&mut Transform, (With<HighlightedHex>, Without<HexTile>)>
+) {
+    if !layout.is_changed() {
+        return;
+    }
+    for (hex_coord, mut transform) in tiles.iter_mut() {
+        *transform = HexTile::transform(*hex_coord, &layout, 0.0);
+    }
+    if let Some(highlighted) = highlighted {
+        for mut transform in highlight.iter_mut() {
+            *transform = highlighted.transform(&layout);
+        }
+    }
 }
 
 #[derive(Resource)]
 pub struct Highlighted(Option<HexCoord>);
 
+impl Highlighted {
+    /// Over the highlighted tile, or tucked under the center tile when nothing is highlighted
+    fn transform(&self, layout: &HexLayout) -> Transform {
+        match self.0 {
+            Some(high_coord) => HexTile::transform(high_coord, layout, 2.0),
+            None => HexTile::transform(HexCoord(0,0), layout, -1.0)
+        }
+    }
+}
+
 fn init_highlighted(
     mut commands: Commands,
     assets: Res<AssetServer>,
+    layout: Res<HexLayout>,
     query: Query<Entity, With<HexGrid>>
 ) {
-    commands.insert_resource(Highlighted(None));
-    
-    let (x, y) = HexCoord(0,0).to_world();
+    let highlighted = Highlighted(None);
     let highlighted_hex = commands.spawn(SpriteBundle {
         texture: assets.load("textures/sprites/hex_highlighted.png"),
-        transform: Transform::from_xyz(x, y, -1.0)
-                    .with_scale(Vec3::new(HEX_SPRITE_SCALE, HEX_SPRITE_SCALE, 1.0)),
+        transform: highlighted.transform(&layout),
         ..default()
     })
     .insert(Name::new("Highlighted Hex"))
     .insert(HighlightedHex)
     .id();
+    commands.insert_resource(highlighted);
 
     commands.entity(query.single()).add_child(highlighted_hex);
 }
@@ -151,11 +136,13 @@ fn highlight_on_click(
     mut highlighted: ResMut<Highlighted>,
     buttons: Res<Input<MouseButton>>,
     mouse_pos: Res<MousePos>,
+    layout: Res<HexLayout>,
 ) {
     if buttons.just_pressed(MouseButton::Left) {
         let mut high_transform = query.single_mut();
 
-        let mouse_hex = HexCoord::from_world(mouse_pos.get_world_coords());
+        let (mouse_x, mouse_y) = mouse_pos.get_world_coords();
+        let mouse_hex = layout.from_pixel(Vec2::new(mouse_x, mouse_y));
         match &highlighted.0 {
             Some(high_coord) => {
                 if *high_coord == mouse_hex {
@@ -166,15 +153,6 @@ fn highlight_on_click(
             },
             None => highlighted.0 = Some(mouse_hex)
         }
-        match &highlighted.0 {
-            Some(high_coord) => {
-                let (x, y) = high_coord.to_world();
-                high_transform.translation = Vec3::new(x,y,2.0);
-            },
-            None => {
-                let (x, y) = HexCoord(0,0).to_world();
-                high_transform.translation = Vec3::new(x,y,-1.0);
-            } 
-        }
+        *high_transform = highlighted.transform(&layout);
     }
 }
\ No newline at end of file
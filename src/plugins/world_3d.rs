@@ -2,6 +2,7 @@ pub mod camera;
 pub mod config;
 pub mod debug;
 pub mod hex;
+pub mod movement;
 pub mod player;
 pub mod sky;
 pub mod transformation;
@@ -19,5 +20,6 @@ impl PluginGroup for World3dPlugins {
         .add(sky::SkyPlugin)
         .add(player::PlayerPlugin)
         .add(transformation::TransformationPlugin)
+        .add(movement::MovementPlugin)
     }
 }
\ No newline at end of file
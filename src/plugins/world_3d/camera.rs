@@ -3,7 +3,13 @@ use bevy::input::mouse::{MouseMotion, MouseWheel};
 use bevy_inspector_egui::Inspectable;
 use bevy_mod_picking::PickingCameraBundle;
 
-use crate::plugins::world_3d::config::*;
+use crate::hex::{HexCoord, HexLayout, HexShape, HexWrap};
+use crate::plugins::world_3d::{
+    config::*,
+    hex::{HexTile, relayout_tiles},
+    player::Player,
+    transformation::Transformation,
+};
 
 pub struct CameraPlugin;
 impl Plugin for CameraPlugin {
@@ -11,7 +17,8 @@ impl Plugin for CameraPlugin {
         app
         .add_startup_system(spawn_camera)
         .add_system(orbit_camera)
-        .add_system(pan_camera);
+        .add_system(pan_camera)
+        .add_system(wrap_around_camera.after(relayout_tiles));
     }
 }
 
@@ -34,18 +41,25 @@ impl Default for PanOrbitCamera {
 }
 
 /// Spawn a camera like this
-fn spawn_camera(mut commands: Commands) {
-    let translation = Vec3::new(0., 20., 10.0);
-    let radius = translation.length();
+fn spawn_camera(
+    mut commands: Commands,
+    layout: Res<HexLayout>,
+    shape: Res<HexShape>
+) {
+    // look at the middle of the board
+    let focus = layout.to_world(shape.center().unwrap_or(HexCoord(0,0)));
+    let offset = Vec3::new(0., 20., 10.0);
+    let radius = offset.length();
 
     commands
     .spawn(Camera3dBundle {
-        transform: Transform::from_translation(translation)
-            .looking_at(Vec3::ZERO, Vec3::Y),
+        transform: Transform::from_translation(focus + offset)
+            .looking_at(focus, Vec3::Y),
         ..Default::default()})
     .insert(PanOrbitCamera {
-        radius,
-        ..Default::default()})
+        focus,
+        radius
+    })
     .insert(Name::new("Game Camera"))
     .insert(PickingCameraBundle::default());
 }
@@ -151,4 +165,49 @@ fn get_primary_window_size(windows: &Res<Windows>) -> Vec2 {
     let window = windows.get_primary().unwrap();
     let window = Vec2::new(window.width() as f32, window.height() as f32);
     window
-}
\ No newline at end of file
+}
+
+/// On wrapping maps, moves every tile to the copy of itself closest to the camera
+/// so the camera can pan forever without reaching an edge or seeing the seam.
+/// Pieces are moved too, but only while they are standing still
+#[allow(clippy::type_complexity)]
+fn wrap_around_camera(
+    wrap: Res<HexWrap>,
+    layout: Res<HexLayout>,
+    camera_query: Query<&PanOrbitCamera>,
+    mut tile_query: Query<(&HexCoord, &mut Transform), With<HexTile>>,
+    mut piece_query: Query<&mut Transform, (With<Player>, Without<HexTile>, Without<Transformation>)>,
+    mut last_focus: Local<Option<HexCoord>>,
+) {
+    if *wrap == HexWrap::None {
+        return;
+    }
+    let camera = match camera_query.get_single() {
+        Ok(camera) => camera,
+        Err(_) => return
+    };
+    let focus = layout.from_world(camera.focus);
+
+    for mut transform in piece_query.iter_mut() {
+        let coord = layout.from_world(transform.translation);
+        let image = wrap.nearest_image(focus, coord);
+        if image != coord {
+            transform.translation += layout.to_world(image) - layout.to_world(coord);
+        }
+    }
+
+    // tiles only need to move when the camera crosses into a new tile,
+    // or when a new layout has put them all back where they started
+    if layout.is_changed() {
+        *last_focus = None;
+    }
+    if *last_focus == Some(focus) {
+        return;
+    }
+    *last_focus = Some(focus);
+    for (coord, mut transform) in tile_query.iter_mut() {
+        let image = layout.to_world(wrap.nearest_image(focus, *coord));
+        transform.translation.x = image.x;
+        transform.translation.z = image.z;
+    }
+}
@@ -23,4 +23,17 @@ pub const SUN_AMBIENT_LIGHT: f32 = 1.;
 
 // Player
 pub const PLAYER_SCALE: f32 = 0.25;
-pub const PLAYER_SPEED: f32 = 0.005;
\ No newline at end of file
+pub const PLAYER_SPEED: f32 = 0.005;
+pub const HOP_HEIGHT: f32 = 0.3; // how far above the higher tile a hop between tiles peaks
+pub const MAX_HOP_HEIGHT: f32 = 1.; // height differences bigger than this are climbed instead of hopped
+pub const CLIMB_SPEED_FACTOR: f32 = 0.5; // climbing is this much slower than walking
+
+// Movement
+pub const MOVE_COST: u32 = 2;
+pub const CLIMB_COST: u32 = 2; // per height level climbed
+pub const DESCEND_COST: u32 = 1; // per height level descended
+pub const MAX_CLIMB: u32 = 4; // highest step up a piece can take, anything taller is a cliff
+pub const MAX_DROP: u32 = 6; // furthest step down a piece can take, anything deeper is a cliff
+pub const SAFE_DROP: u32 = 3; // drops further than this cause fall damage
+pub const FALL_DAMAGE_PER_LEVEL: u32 = 1; // damage for each height level past SAFE_DROP
+pub const PLAYER_MOVE_BUDGET: u32 = 20; // total movement cost the player can spend in one move
\ No newline at end of file
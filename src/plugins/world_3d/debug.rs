@@ -1,10 +1,8 @@
 use bevy::prelude::*;
 use bevy_inspector_egui::{RegisterInspectable, WorldInspectorPlugin};
 
-use crate::plugins::world_3d::{
-    player::Player,
-    hex::HexCoord
-};
+use crate::hex::HexCoord;
+use crate::plugins::world_3d::player::Player;
 
 pub struct DebugPlugin;
 
@@ -1,13 +1,11 @@
 pub mod height_map;
 
-// Standard Lib Imports
-use std::cmp::{max,min};
-
 // Bevy Imports
 use bevy::prelude::*;
 use bevy_inspector_egui::Inspectable;
-use bevy_mod_picking::{PickableBundle, PickingEvent, SelectionEvent};
+use bevy_mod_picking::PickableBundle;
 
+use crate::hex::{HexCoord, HexLayout, HexOrientation, HexShape, HexWrap, WorldPlane};
 use crate::plugins::world_3d::config::{HEX_CIRCUMRADIUS, HEX_GRID_RADIUS};
 use height_map::HeightMap;
 
@@ -16,13 +14,39 @@ pub struct HexPlugin;
 impl Plugin for HexPlugin {
     fn build(&self, app: &mut App) {
         app
+        .insert_resource(HexLayout::new(HexOrientation::Pointy, HEX_CIRCUMRADIUS, WorldPlane::XZ))
+        .insert_resource(HexWrap::None)
+        // .insert_resource(HexWrap::cylinder(40, 30))
+        // .insert_resource(HexWrap::torus(40, 30))
+        .add_startup_system_to_stage(StartupStage::PreStartup, init_map_shape)
         .add_startup_system_to_stage(StartupStage::PreStartup, init_height_map)
-        .add_startup_system(HexGrid::spawn);
+        .add_startup_system(bake_height_map.before(HexGrid::spawn))
+        .add_startup_system(HexGrid::spawn)
+        .add_system(relayout_tiles);
     }
 }
 
+fn init_map_shape(
+    mut commands: Commands,
+    wrap: Res<HexWrap>,
+) {
+    // wrapping maps need the rectangle they wrap around
+    if let Some(shape) = wrap.shape() {
+        commands.insert_resource(shape);
+        return;
+    }
+    commands
+    .insert_resource(HexShape::Hexagon { radius: HEX_GRID_RADIUS })
+    // .insert_resource(HexShape::Rectangle { width: 40, height: 30, offset: crate::hex::OffsetKind::OddR })
+    // .insert_resource(HexShape::Parallelogram { width: 30, height: 20 })
+    // .insert_resource(HexShape::Triangle { size: 30 })
+    // .insert_resource(HexShape::mask_from_file("assets/maps/island.txt", crate::hex::OffsetKind::OddR).unwrap())
+    ;
+}
+
 fn init_height_map(
     mut commands: Commands,
+    wrap: Res<HexWrap>,
 ) {
     commands
     // .insert_resource(HeightMap::new(height_map::FlatGenerator::new(1)))
@@ -31,105 +55,32 @@ fn init_height_map(
     // .insert_resource(HeightMap::new(height_map::PerlinGenerator::hills(None)))
     // .insert_resource(HeightMap::new(height_map::PerlinGenerator::slopes(None)))
     // .insert_resource(HeightMap::new(height_map::PerlinGenerator::crags(None)))
-    .insert_resource(HeightMap::new(height_map::PerlinGenerator::lowlands(None)))
+    .insert_resource(HeightMap::new(height_map::PerlinGenerator::lowlands(None)).with_wrap(*wrap))
     // .insert_resource(HeightMap::new(height_map::PerlinGenerator::new(vec![
     //     height_map::PerlinStep::new(0.05, 0.035, 3.)
     // ], None)))
+    // .insert_resource(HeightMap::new(height_map::SimplexGenerator::rolling_hills(None)))
+    // .insert_resource(HeightMap::new(height_map::RidgedGenerator::mountains(None)))
+    // .insert_resource(HeightMap::new(height_map::BillowGenerator::foothills(None)))
+    // .insert_resource(HeightMap::new(height_map::WarpedGenerator::winding_mountains(None)))
+    // .insert_resource(HeightMap::new(height_map::Blend::new(
+    //     height_map::PerlinGenerator::lowlands(None),
+    //     height_map::Terrace::new(height_map::PerlinGenerator::crags(None), 6, 60),
+    //     height_map::PerlinGenerator::slopes(None),
+    //     60
+    // )))
+    // .insert_resource(HeightMap::new(height_map::IslandGenerator::continent(height_map::SimplexGenerator::highlands(None), None)))
+    // .insert_resource(HeightMap::new(height_map::ArchipelagoGenerator::isles(height_map::SimplexGenerator::rolling_hills(None), None)))
+    // .insert_resource(HeightMap::new(height_map::ErodedGenerator::weathered(height_map::PerlinGenerator::hills(None), None)))
     ;
 }
 
-#[derive(Component, Inspectable, Debug, Copy, Clone)]
-/// Coordinates in axial space
-/// see: https://www.redblobgames.com/grids/hexagons/#coordinates-axial
-/// HexCoord(q, r)
-pub struct HexCoord(pub i32, pub i32);
-
-impl HexCoord {
-    /// see: https://www.redblobgames.com/grids/hexagons/#hex-to-pixel-axial
-    /// 
-    /// Optionally provide height map to get position at the top of the tile.
-    /// Otherwise just set height to 0
-    pub fn to_world(&self, map: Option<&HeightMap>) -> Vec3 {
-        let x = HEX_CIRCUMRADIUS * f32::sqrt(3.0) * ((self.0 as f32) + (self.1 as f32) / 2.0);
-        let y = if let Some(map) = map {map.get_world_height(*self)} else {0.};
-        let z = HEX_CIRCUMRADIUS * (3.0/2.0) * (self.1 as f32);
-        return Vec3 { x, y, z };
-    }
-    
-    /// Uses just x and z componeents of world coord to convert to hexcoord
-    /// See: https://www.redblobgames.com/grids/hexagons/#pixel-to-hex
-    pub fn from_world(world_coord: Vec3) -> HexCoord {
-        // first convert to hex space
-        let x = (f32::sqrt(3.0)*world_coord.x - world_coord.z) / 3.0 / HEX_CIRCUMRADIUS;
-        let y = ((2.0/3.0) * world_coord.z) / HEX_CIRCUMRADIUS;
-        // then round it to the nearest hex coord
-        HexCoord::from_floating((x,y))
-    }
-
-    /// Round floating point hex space coords to integer hexcoord
-    /// see: https://www.redblobgames.com/grids/hexagons/#rounding
-    pub fn from_floating((fx, fy): (f32, f32)) -> HexCoord {
-        let mut x = fx.round();
-        let mut y = fy.round();
-        let rem_x = fx - x;
-        let rem_y = fy - y;
-        if rem_x.abs() >= rem_y.abs() {
-            x += (rem_x + 0.5*rem_y).round();
-        } else {
-            y += (rem_y + 0.5*rem_x).round();
-        }
-        HexCoord(x as i32, y as i32)
-    }
-
-    pub fn to_bytes(self) -> [u8; 8] {
-        let x:[u8; 4] = self.0.to_ne_bytes();
-        let y:[u8; 4] = self.1.to_ne_bytes();
-        let concat = [x[0],x[1],x[2],x[3],y[0],y[1],y[2],y[3]];
-        concat
-    }
-
-    /// Distance in hex space to other coord.
-    /// See: https://www.redblobgames.com/grids/hexagons/#distances-axial
-    pub fn distance(&self, other: HexCoord) -> u64 {
-        ( (self.0 - other.0).abs()
-        + (self.0 + self.1 - other.0 - other.1).abs()
-        + (self.1 - other.1).abs()
-        ) as u64 / 2
-    }
-
-    /// Gets the hexcoords that draw a straight line between self and other
-    /// See: https://www.redblobgames.com/grids/hexagons/#line-drawing
-    pub fn line_between(&self, other: HexCoord) -> Vec<HexCoord> {
-        let start_world = self.to_world(None);
-        let end_world = other.to_world(None);
-        
-        let dist = self.distance(other);
-        let mut results = Vec::new();
-        for point in 0..=dist {
-            let inter_world = start_world.lerp(end_world, (point as f32) / (dist as f32));
-            let inter_hex = HexCoord::from_world(inter_world);
-            results.push(inter_hex);
-        }
-        results
-    }
-
-    /// returns all the hex coords that are
-    /// within radius number of tiles
-    pub fn within_radius(&self, radius: i32) -> Vec<HexCoord> {
-        let mut within = Vec::new();
-        for x in -radius..radius+1 {
-            for y in max(-radius, (-x)-radius)..min(radius,(-x)+radius)+1 {
-                within.push(HexCoord(x+self.0, y+self.1));
-            }
-        }
-        return within;
-    }
-}
-
-impl PartialEq for HexCoord {
-    fn eq(&self, other: &Self) -> bool {
-        self.0 == other.0 && self.1 == other.1
-    }
+/// Stores the height of every tile on the map so they aren't generated again on each lookup
+fn bake_height_map(
+    mut height_map: ResMut<HeightMap>,
+    shape: Res<HexShape>,
+) {
+    height_map.bake(shape.tiles());
 }
 
 #[derive(Component, Inspectable)]
@@ -140,15 +91,21 @@ pub struct HexGrid;
         mut commands: Commands,
         assets: Res<AssetServer>,
         mut materials: ResMut<Assets<StandardMaterial>>,
-        height_map: Res<HeightMap>
+        height_map: Res<HeightMap>,
+        layout: Res<HexLayout>,
+        shape: Res<HexShape>
     ) {
 
         let tile_material = materials.add(Color::rgb(1., 0.8, 0.8).into());
+        commands.insert_resource(TileMaterials {
+            normal: tile_material.clone(),
+            reachable: materials.add(Color::rgb(0.6, 0.9, 0.6).into()),
+        });
         let hex_tile_mesh: Handle<Mesh> = assets.load("meshes/hex.glb#Mesh0/Primitive0");
 
         let mut tiles = Vec::new();
-        for hex_coord in HexCoord(0,0).within_radius(HEX_GRID_RADIUS).into_iter() {
-            let tile = HexTile::spawn(hex_coord, &height_map, &mut commands, &hex_tile_mesh, &tile_material);
+        for hex_coord in shape.tiles() {
+            let tile = HexTile::spawn(hex_coord, &height_map, &layout, &mut commands, &hex_tile_mesh, &tile_material);
             tiles.push(tile);
         }
         commands
@@ -163,28 +120,28 @@ pub struct HexGrid;
 #[derive(Component, Inspectable)]
 pub struct HexTile;
 
+/// Materials tiles can be drawn with
+#[derive(Resource)]
+pub struct TileMaterials {
+    pub normal: Handle<StandardMaterial>,
+    /// tiles the selected piece can move to
+    pub reachable: Handle<StandardMaterial>
+}
+
 impl HexTile {
     fn spawn(
         hex_coord: HexCoord,
         height_map: &HeightMap,
+        layout: &HexLayout,
         commands: &mut Commands,
         mesh: &Handle<Mesh>,
         material: &Handle<StandardMaterial>
     ) -> Entity {
-        let height = height_map.get_world_height(hex_coord);
-        let mut position = hex_coord.to_world(None);
-        position.y = height / 2.;
-        let scale = Vec3::new(1.,height,1.);
         commands
             .spawn(PbrBundle {
                 mesh: mesh.clone(),
                 material: material.clone(),
-                transform: Transform {
-                    translation: position,
-                    scale,
-                    ..default()
-                }
-                ,
+                transform: Self::transform(hex_coord, height_map, layout),
                 ..Default::default()
             })
             .insert(Name::new("HexTile"))
@@ -193,4 +150,33 @@ impl HexTile {
             .insert(PickableBundle::default())
             .id()
     }
-}
\ No newline at end of file
+
+    /// Column for hex_coord standing on the ground and reaching up to its height
+    fn transform(hex_coord: HexCoord, height_map: &HeightMap, layout: &HexLayout) -> Transform {
+        let height = height_map.get_world_height(hex_coord);
+        let mut position = layout.to_world(hex_coord);
+        position.y = height / 2.;
+        // the tile mesh is modelled at HEX_CIRCUMRADIUS so stretch it to the layout size
+        let width = layout.size / HEX_CIRCUMRADIUS;
+        Transform {
+            translation: position,
+            rotation: Quat::from_rotation_y(layout.orientation.tile_angle()),
+            scale: Vec3::new(width, height, width)
+        }
+    }
+}
+
+/// Moves and resizes the tiles when the layout changes.
+/// Puts every tile back on its canonical position, so runs before the camera wraps the map around
+pub(crate) fn relayout_tiles(
+    layout: Res<HexLayout>,
+    height_map: Res<HeightMap>,
+    mut tiles: Query<(&HexCoord, &mut Transform), With<HexTile>>
+) {
+    if !layout.is_changed() {
+        return;
+    }
+    for (hex_coord, mut transform) in tiles.iter_mut() {
+        *transform = HexTile::transform(*hex_coord, &height_map, &layout);
+    }
+}
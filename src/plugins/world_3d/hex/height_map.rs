@@ -1,10 +1,34 @@
+pub mod baked;
+pub mod combinators;
+pub mod erosion;
+pub mod falloff;
+pub mod fractal;
+pub mod grayscale;
+pub mod noise;
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
 use bevy::prelude::*;
 use xxhash_rust::xxh3::xxh3_64_with_seed;
 
-use crate::plugins::world_3d::{
-    config::HEX_HEIGHT_SCALE,
-    hex::HexCoord
+use crate::hex::{HexCoord, HexLayout, HexWrap};
+use crate::plugins::world_3d::config::HEX_HEIGHT_SCALE;
+use noise::PermutationTable;
+
+pub use baked::BakedHeights;
+pub use combinators::{Add, Blend, Clamp, Invert, Max, Min, Offset, Scale, Terrace};
+pub use erosion::{ErodedGenerator, HeightField, HydraulicErosion, ThermalErosion};
+pub use falloff::{ArchipelagoGenerator, Coastline, Falloff, IslandGenerator};
+pub use fractal::{
+    BillowGenerator,
+    Fractal,
+    NoiseField,
+    RidgedGenerator,
+    SimplexGenerator,
+    WarpedGenerator,
 };
+pub use grayscale::{export_png, ImageGenerator};
 
 /// hashes bytes with seed using msg
 /// to distinguish it from other hashes on same bytes
@@ -23,14 +47,33 @@ pub fn to_world(height: u32) -> f32 {
 
 // ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Wrapper Struct ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
 
+/// Heights of every tile. Generated once and stored.
+/// Tiles given to `bake` are kept in a dense grid and any others are generated and cached the first time they're asked for
 #[derive(Resource)]
 pub struct HeightMap {
-    generator: Box<dyn HeightGenerator>
+    generator: Box<dyn HeightGenerator>,
+    wrap: HexWrap,
+    baked: BakedHeights,
+    /// heights asked for outside the baked tiles
+    cache: RwLock<HashMap<HexCoord, u32>>
 }
 
 impl HeightMap {
     /// returns as a quantized integer. To get this as height in world space. Use `get_world_height`
     pub fn get_height(&self, coord: HexCoord) -> u32 {
+        let coord = self.wrap.canonical(coord);
+        if let Some(height) = self.baked.get(coord) {
+            return height;
+        }
+        if let Some(height) = self.cache.read().unwrap().get(&coord) {
+            return *height;
+        }
+        let height = self.generate(coord);
+        self.cache.write().unwrap().insert(coord, height);
+        height
+    }
+
+    fn generate(&self, coord: HexCoord) -> u32 {
         std::cmp::max(self.generator.generate_height(coord), 1)
     }
 
@@ -40,8 +83,43 @@ impl HeightMap {
         to_world(q_height)
     }
 
+    /// Position at the top of the tile in world space
+    pub fn world_position(&self, coord: HexCoord, layout: &HexLayout) -> Vec3 {
+        let mut position = layout.to_world(coord);
+        position.y = self.get_world_height(coord);
+        position
+    }
+
+    /// Generates and stores the height of every tile up front. Use the tiles of the map shape
+    pub fn bake(&mut self, tiles: impl IntoIterator<Item = HexCoord>) {
+        let heights: Vec<(HexCoord, u32)> = tiles.into_iter()
+            .map(|tile| self.wrap.canonical(tile))
+            .map(|tile| (tile, self.generate(tile)))
+            .collect();
+        self.baked = BakedHeights::new(heights);
+        self.cache.get_mut().unwrap().clear();
+    }
+
+    /// Every baked tile and its quantized height
+    pub fn tiles(&self) -> impl Iterator<Item = (HexCoord, u32)> + '_ {
+        self.baked.iter()
+    }
+
     pub fn new(generator: impl HeightGenerator) -> Self {
-        Self {generator: Box::new(generator)}
+        Self {
+            generator: Box::new(generator),
+            wrap: HexWrap::None,
+            baked: default(),
+            cache: default()
+        }
+    }
+
+    /// Makes every copy of a tile on a wrapping map share the same height
+    pub fn with_wrap(mut self, wrap: HexWrap) -> Self {
+        self.wrap = wrap;
+        self.baked = default();
+        self.cache.get_mut().unwrap().clear();
+        self
     }
 }
 
@@ -49,6 +127,51 @@ impl HeightMap {
 
 pub trait HeightGenerator: Send + Sync + 'static {
     fn generate_height(&self, coord: HexCoord) -> u32;
+
+    // ~~~~~~~~~~~~~~ Combinators ~~~~~~~~~~~~~~ //
+
+    fn plus<G: HeightGenerator>(self, other: G) -> Add<Self, G> where Self: Sized {
+        Add::new(self, other)
+    }
+
+    fn scaled(self, factor: f32) -> Scale<Self> where Self: Sized {
+        Scale::new(self, factor)
+    }
+
+    fn offset(self, offset: i32) -> Offset<Self> where Self: Sized {
+        Offset::new(self, offset)
+    }
+
+    fn clamped(self, min: u32, max: u32) -> Clamp<Self> where Self: Sized {
+        Clamp::new(self, min, max)
+    }
+
+    fn terraced(self, steps: u32, ceiling: u32) -> Terrace<Self> where Self: Sized {
+        Terrace::new(self, steps, ceiling)
+    }
+
+    fn inverted(self, ceiling: u32) -> Invert<Self> where Self: Sized {
+        Invert::new(self, ceiling)
+    }
+
+    fn max_with<G: HeightGenerator>(self, other: G) -> Max<Self, G> where Self: Sized {
+        Max::new(self, other)
+    }
+
+    fn min_with<G: HeightGenerator>(self, other: G) -> Min<Self, G> where Self: Sized {
+        Min::new(self, other)
+    }
+
+    /// Fades from self to other where mask goes from 0 to mask_max
+    fn blend<G: HeightGenerator, M: HeightGenerator>(self, other: G, mask: M, mask_max: u32) -> Blend<Self, G, M> where Self: Sized {
+        Blend::new(self, other, mask, mask_max)
+    }
+}
+
+impl HeightGenerator for Box<dyn HeightGenerator> {
+    fn generate_height(&self, coord: HexCoord) -> u32 {
+        self.as_ref().generate_height(coord)
+    }
 }
 
 pub struct FlatGenerator {
@@ -93,7 +216,7 @@ impl HeightGenerator for RandGenerator {
 // generate terrain height with fractal perlin noise
 pub struct PerlinGenerator {
     steps: Vec<PerlinStep>,
-    seed: u64
+    table: PermutationTable
 }
 
 impl PerlinGenerator {
@@ -101,7 +224,7 @@ impl PerlinGenerator {
     // For creating custom perlin height maps
     pub fn new(steps: Vec<PerlinStep>, seed: Option<u64>) -> Self {
         let seed = seed.unwrap_or(rand::random());
-        Self{steps,seed}
+        Self{steps, table: PermutationTable::new(seed)}
     }
 
     // ~~~~~~~~~~~~~~ Prefabs ~~~~~~~~~~~~~~ //
@@ -136,42 +259,6 @@ impl PerlinGenerator {
             PerlinStep::new(0.035, 0.05, 3.)
         ], seed)
     }
-
-    // ~~~~~~~~~~~ Internal Funcs ~~~~~~~~~~~ //
-    // These were created by following https://gpfault.net/posts/perlin-noise.txt.html
-
-    fn gradient(&self, vec: Vec2) -> Vec2 {
-        let x_dir = seeded_hash(vec.to_string().as_bytes(), self.seed, "Perlin X Dir") as f32;
-        let y_dir = seeded_hash(vec.to_string().as_bytes(), self.seed, "Perlin Y Dir") as f32;
-        Vec2::new(x_dir, y_dir).normalize()
-    }
-
-    fn fade(p: f32) -> f32 {
-        p*p*p*(p*(p*6. - 15.) + 10.)
-    }
-
-    fn noise(&self, v: Vec2) -> f32 {
-        let v0 = v.floor();
-        let v1 = v0 + Vec2::new(1.,0.);
-        let v2 = v0 + Vec2::new(0.,1.);
-        let v3 = v0 + Vec2::new(1.,1.);
-
-        let g0 = self.gradient(v0);
-        let g1 = self.gradient(v1);
-        let g2 = self.gradient(v2);
-        let g3 = self.gradient(v3);
-
-        let t0 = v.x - v0.x;
-        let t1 = v.y - v0.y;
-
-        let fade_t0 = Self::fade(t0);
-        let fade_t1 = Self::fade(t1);
-
-        let v0v1 = (1. - fade_t0) * g0.dot(v - v0) + fade_t0 * g1.dot(v - v1);
-        let v2v3 = (1. - fade_t0) * g2.dot(v - v2) + fade_t0 * g3.dot(v - v3);
-
-        (1. - fade_t1) * v0v1 + fade_t1 * v2v3
-    }
 }
 
 impl HeightGenerator for PerlinGenerator {
@@ -180,7 +267,7 @@ impl HeightGenerator for PerlinGenerator {
         for step in self.steps.iter() {
             let x = (coord.0 as f32) * step.x_freq;
             let y = (coord.1 as f32) * step.y_freq;
-            let noise = self.noise(Vec2::new(x, y));
+            let noise = self.table.perlin(Vec2::new(x, y));
             height += (noise * 2. + 0.7) * step.magnitude;
             height += noise * step.magnitude;
         }
@@ -0,0 +1,134 @@
+//! Flat storage for heights that have already been generated
+use crate::hex::HexCoord;
+
+/// Heights laid out in a grid over the axial bounding box of the tiles baked into it.
+/// Lookups are an index into a `Vec` instead of running a generator again
+#[derive(Debug, Clone, Default)]
+pub struct BakedHeights {
+    /// smallest (q, r) of any baked tile
+    min: (i32, i32),
+    /// tiles per row of the bounding box
+    width: usize,
+    /// row by row, None for spots in the box that weren't baked
+    heights: Vec<Option<u32>>
+}
+
+impl BakedHeights {
+    pub fn new(heights: impl IntoIterator<Item = (HexCoord, u32)>) -> Self {
+        let heights: Vec<(HexCoord, u32)> = heights.into_iter().collect();
+        if heights.is_empty() {
+            return Self::default();
+        }
+        let min_q = heights.iter().map(|(coord, _)| coord.q()).min().unwrap();
+        let max_q = heights.iter().map(|(coord, _)| coord.q()).max().unwrap();
+        let min_r = heights.iter().map(|(coord, _)| coord.r()).min().unwrap();
+        let max_r = heights.iter().map(|(coord, _)| coord.r()).max().unwrap();
+        let width = (max_q - min_q + 1) as usize;
+        let rows = (max_r - min_r + 1) as usize;
+
+        let mut baked = Self { min: (min_q, min_r), width, heights: vec![None; width * rows] };
+        for (coord, height) in heights {
+            if let Some(i) = baked.index(coord) {
+                baked.heights[i] = Some(height);
+            }
+        }
+        baked
+    }
+
+    fn index(&self, coord: HexCoord) -> Option<usize> {
+        if self.width == 0 {
+            return None;
+        }
+        // i64 so coords far from the box can't overflow
+        let q = coord.q() as i64 - self.min.0 as i64;
+        let r = coord.r() as i64 - self.min.1 as i64;
+        let rows = self.heights.len() / self.width;
+        if q < 0 || r < 0 || q as usize >= self.width || r as usize >= rows {
+            return None;
+        }
+        Some(r as usize * self.width + q as usize)
+    }
+
+    pub fn get(&self, coord: HexCoord) -> Option<u32> {
+        match self.index(coord) {
+            Some(i) => self.heights[i],
+            None => None
+        }
+    }
+
+    /// Every baked tile and its height
+    pub fn iter(&self) -> impl Iterator<Item = (HexCoord, u32)> + '_ {
+        self.heights.iter().enumerate().filter_map(|(i, height)| {
+            let q = self.min.0 + (i % self.width) as i32;
+            let r = self.min.1 + (i / self.width) as i32;
+            height.map(|height| (HexCoord(q, r), height))
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.heights.iter().filter(|height| height.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heights.iter().all(|height| height.is_none())
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Tests ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::hex::HexRegion;
+    use crate::plugins::world_3d::hex::height_map::{HeightGenerator, SimplexGenerator};
+
+    /// Two blobs on the negative side, so the box has gaps between them
+    fn tiles() -> HexRegion {
+        HexRegion::hexagon(HexCoord(-6, -3), 3).union(&HexRegion::hexagon(HexCoord(4, -9), 2))
+    }
+
+    fn bake(generator: &impl HeightGenerator) -> BakedHeights {
+        BakedHeights::new(tiles().sorted().into_iter().map(|tile| (tile, generator.generate_height(tile))))
+    }
+
+    #[test]
+    fn matches_the_generator() {
+        let generator = SimplexGenerator::rolling_hills(Some(5));
+        let baked = bake(&generator);
+        assert_eq!(baked.len(), tiles().len());
+        for tile in tiles().iter() {
+            assert_eq!(baked.get(tile), Some(generator.generate_height(tile)), "{:?}", tile);
+        }
+        let expected: HashMap<HexCoord, u32> = tiles().iter()
+            .map(|tile| (tile, generator.generate_height(tile)))
+            .collect();
+        assert_eq!(baked.iter().collect::<HashMap<_, _>>(), expected);
+        assert_eq!(baked.iter().count(), expected.len());
+    }
+
+    #[test]
+    fn nothing_outside_the_baked_tiles() {
+        let baked = bake(&SimplexGenerator::rolling_hills(Some(5)));
+        let tiles = tiles();
+        // covers the gaps inside the box and plenty past every side of it
+        for coord in HexCoord(0, -5).spiral(20) {
+            if !tiles.contains(coord) {
+                assert_eq!(baked.get(coord), None, "{:?}", coord);
+            }
+        }
+        for coord in [HexCoord(i32::MIN, i32::MIN), HexCoord(i32::MAX, i32::MIN), HexCoord(-9, i32::MAX)] {
+            assert_eq!(baked.get(coord), None, "{:?}", coord);
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let baked = BakedHeights::new([]);
+        assert!(baked.is_empty());
+        assert_eq!(baked.len(), 0);
+        assert_eq!(baked.get(HexCoord(0, 0)), None);
+        assert_eq!(baked.iter().count(), 0);
+    }
+}
@@ -0,0 +1,221 @@
+//! Height generators built out of other height generators.
+//!
+//! Usually made through the helper methods on `HeightGenerator`:
+//! `PerlinGenerator::hills(None).scaled(0.5).plus(RandGenerator::new(0, 2, None))`
+use crate::hex::HexCoord;
+use super::HeightGenerator;
+
+/// Sum of two generators
+pub struct Add<A: HeightGenerator, B: HeightGenerator> {
+    a: A,
+    b: B
+}
+
+impl<A: HeightGenerator, B: HeightGenerator> Add<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Self { a, b }
+    }
+}
+
+impl<A: HeightGenerator, B: HeightGenerator> HeightGenerator for Add<A, B> {
+    fn generate_height(&self, coord: HexCoord) -> u32 {
+        self.a.generate_height(coord).saturating_add(self.b.generate_height(coord))
+    }
+}
+
+/// Multiplies heights by a factor, rounding down
+pub struct Scale<G: HeightGenerator> {
+    generator: G,
+    factor: f32
+}
+
+impl<G: HeightGenerator> Scale<G> {
+    pub fn new(generator: G, factor: f32) -> Self {
+        Self { generator, factor }
+    }
+}
+
+impl<G: HeightGenerator> HeightGenerator for Scale<G> {
+    fn generate_height(&self, coord: HexCoord) -> u32 {
+        (self.generator.generate_height(coord) as f32 * self.factor) as u32
+    }
+}
+
+/// Raises or lowers every height. Heights stop at 0
+pub struct Offset<G: HeightGenerator> {
+    generator: G,
+    offset: i32
+}
+
+impl<G: HeightGenerator> Offset<G> {
+    pub fn new(generator: G, offset: i32) -> Self {
+        Self { generator, offset }
+    }
+}
+
+impl<G: HeightGenerator> HeightGenerator for Offset<G> {
+    fn generate_height(&self, coord: HexCoord) -> u32 {
+        let height = self.generator.generate_height(coord);
+        if self.offset < 0 {
+            height.saturating_sub(self.offset.unsigned_abs())
+        } else {
+            height.saturating_add(self.offset as u32)
+        }
+    }
+}
+
+/// Keeps heights within min..=max
+pub struct Clamp<G: HeightGenerator> {
+    generator: G,
+    min: u32,
+    max: u32
+}
+
+impl<G: HeightGenerator> Clamp<G> {
+    pub fn new(generator: G, min: u32, max: u32) -> Self {
+        assert!(min <= max, "clamp min can't be above max");
+        Self { generator, min, max }
+    }
+}
+
+impl<G: HeightGenerator> HeightGenerator for Clamp<G> {
+    fn generate_height(&self, coord: HexCoord) -> u32 {
+        self.generator.generate_height(coord).clamp(self.min, self.max)
+    }
+}
+
+/// Flattens slopes into `steps` plateaus evenly spread under ceiling.
+/// Heights round down to the plateau below them and anything above ceiling joins the top plateau
+pub struct Terrace<G: HeightGenerator> {
+    generator: G,
+    steps: u32,
+    ceiling: u32
+}
+
+impl<G: HeightGenerator> Terrace<G> {
+    pub fn new(generator: G, steps: u32, ceiling: u32) -> Self {
+        assert!(steps > 0, "terraces need at least 1 step");
+        assert!(ceiling >= steps, "terrace ceiling must leave every step at least 1 high");
+        Self { generator, steps, ceiling }
+    }
+}
+
+impl<G: HeightGenerator> HeightGenerator for Terrace<G> {
+    fn generate_height(&self, coord: HexCoord) -> u32 {
+        let height = self.generator.generate_height(coord) as u64;
+        let (steps, ceiling) = (self.steps as u64, self.ceiling as u64);
+        let step = (height * steps / ceiling).min(steps - 1);
+        (step * ceiling / steps) as u32
+    }
+}
+
+/// Turns terrain upside down so peaks become pits.
+/// Heights are flipped under ceiling and anything above it becomes 0
+pub struct Invert<G: HeightGenerator> {
+    generator: G,
+    ceiling: u32
+}
+
+impl<G: HeightGenerator> Invert<G> {
+    pub fn new(generator: G, ceiling: u32) -> Self {
+        Self { generator, ceiling }
+    }
+}
+
+impl<G: HeightGenerator> HeightGenerator for Invert<G> {
+    fn generate_height(&self, coord: HexCoord) -> u32 {
+        self.ceiling.saturating_sub(self.generator.generate_height(coord))
+    }
+}
+
+/// Higher of two generators
+pub struct Max<A: HeightGenerator, B: HeightGenerator> {
+    a: A,
+    b: B
+}
+
+impl<A: HeightGenerator, B: HeightGenerator> Max<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Self { a, b }
+    }
+}
+
+impl<A: HeightGenerator, B: HeightGenerator> HeightGenerator for Max<A, B> {
+    fn generate_height(&self, coord: HexCoord) -> u32 {
+        u32::max(self.a.generate_height(coord), self.b.generate_height(coord))
+    }
+}
+
+/// Lower of two generators
+pub struct Min<A: HeightGenerator, B: HeightGenerator> {
+    a: A,
+    b: B
+}
+
+impl<A: HeightGenerator, B: HeightGenerator> Min<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Self { a, b }
+    }
+}
+
+impl<A: HeightGenerator, B: HeightGenerator> HeightGenerator for Min<A, B> {
+    fn generate_height(&self, coord: HexCoord) -> u32 {
+        u32::min(self.a.generate_height(coord), self.b.generate_height(coord))
+    }
+}
+
+/// Mixes two generators by a third. Where the mask is 0 it is all a,
+/// where the mask is mask_max or above it is all b
+pub struct Blend<A: HeightGenerator, B: HeightGenerator, M: HeightGenerator> {
+    a: A,
+    b: B,
+    mask: M,
+    mask_max: u32
+}
+
+impl<A: HeightGenerator, B: HeightGenerator, M: HeightGenerator> Blend<A, B, M> {
+    pub fn new(a: A, b: B, mask: M, mask_max: u32) -> Self {
+        assert!(mask_max > 0, "blend mask needs a max above 0");
+        Self { a, b, mask, mask_max }
+    }
+}
+
+impl<A: HeightGenerator, B: HeightGenerator, M: HeightGenerator> HeightGenerator for Blend<A, B, M> {
+    fn generate_height(&self, coord: HexCoord) -> u32 {
+        let t = (self.mask.generate_height(coord) as f32 / self.mask_max as f32).min(1.);
+        let a = self.a.generate_height(coord) as f32;
+        let b = self.b.generate_height(coord) as f32;
+        (a + (b - a) * t).round() as u32
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Tests ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::plugins::world_3d::hex::height_map::{FlatGenerator, RandGenerator};
+
+    #[test]
+    fn terrace_has_steps_plateaus() {
+        // reaches past the ceiling so the top plateau is tested too
+        let terrace = Terrace::new(RandGenerator::new(0, 130, Some(3)), 5, 100);
+        let heights: BTreeSet<u32> = HexCoord::ZERO.spiral(20)
+            .map(|coord| terrace.generate_height(coord))
+            .collect();
+        assert_eq!(heights, BTreeSet::from([0, 20, 40, 60, 80]));
+    }
+
+    #[test]
+    fn terrace_rounds_down() {
+        let terrace = |height| Terrace::new(FlatGenerator::new(height), 4, 10).generate_height(HexCoord::ZERO);
+        assert_eq!(terrace(0), 0);
+        assert_eq!(terrace(2), 0);
+        assert_eq!(terrace(3), 2);
+        assert_eq!(terrace(9), 7);
+        assert_eq!(terrace(10), 7);
+        assert_eq!(terrace(500), 7);
+    }
+}
@@ -0,0 +1,283 @@
+//! Weathers generated terrain by simulating water and loose rock over it.
+//! See: https://github.com/SebLague/Hydraulic-Erosion
+use std::collections::HashMap;
+
+use crate::hex::HexCoord;
+use crate::plugins::world_3d::config::HEX_GRID_RADIUS;
+use super::{HeightGenerator, seeded_hash};
+
+/// Heights of a fixed set of tiles, generated once so they can be changed in place.
+/// Tiles outside the field count as missing, so water and rock never flow off the edge
+pub struct HeightField {
+    tiles: Vec<HexCoord>,
+    index: HashMap<HexCoord, usize>,
+    heights: Vec<f32>
+}
+
+impl HeightField {
+    pub fn bake(generator: &impl HeightGenerator, tiles: impl IntoIterator<Item = HexCoord>) -> Self {
+        let mut field = Self { tiles: Vec::new(), index: HashMap::new(), heights: Vec::new() };
+        for tile in tiles {
+            if field.index.contains_key(&tile) {
+                continue;
+            }
+            field.index.insert(tile, field.tiles.len());
+            field.tiles.push(tile);
+            field.heights.push(generator.generate_height(tile) as f32);
+        }
+        field
+    }
+
+    pub fn get(&self, coord: HexCoord) -> Option<f32> {
+        self.index.get(&coord).map(|i| self.heights[*i])
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Indices of the neighbors of tile i that are in the field
+    fn neighbors(&self, i: usize) -> impl Iterator<Item = usize> + '_ {
+        self.tiles[i].neighbors().into_iter().filter_map(|n| self.index.get(&n).copied())
+    }
+
+    /// Lowest neighbor of tile i and its height
+    fn lowest_neighbor(&self, i: usize) -> Option<(usize, f32)> {
+        self.neighbors(i)
+            .map(|n| (n, self.heights[n]))
+            .fold(None, |lowest, (n, height)| match lowest {
+                Some((_, low)) if low <= height => lowest,
+                _ => Some((n, height))
+            })
+    }
+
+    /// Rolls droplets downhill, each picking up sediment where it speeds up
+    /// and dropping it where it slows or pools. Carves valleys and fills basins
+    pub fn erode_hydraulic(&mut self, erosion: &HydraulicErosion, seed: u64) {
+        if self.is_empty() {
+            return;
+        }
+        for droplet in 0..erosion.droplets {
+            let spawn = seeded_hash(&droplet.to_le_bytes(), seed, "Erosion Droplet");
+            let mut tile = spawn as usize % self.len();
+            let mut speed = 1.;
+            let mut water = 1.;
+            let mut sediment = 0.;
+
+            for _ in 0..erosion.lifetime {
+                let height = self.heights[tile];
+                let (next, next_height) = match self.lowest_neighbor(tile) {
+                    Some((next, next_height)) if next_height < height => (next, next_height),
+                    // stuck in a pit
+                    _ => break
+                };
+
+                let drop = height - next_height;
+                let capacity = f32::max(drop * speed * water * erosion.capacity, erosion.min_capacity);
+                if sediment > capacity {
+                    let deposit = (sediment - capacity) * erosion.deposition;
+                    self.heights[tile] += deposit;
+                    sediment -= deposit;
+                } else {
+                    // never dig below where the water is heading or it would flow back
+                    let taken = f32::min((capacity - sediment) * erosion.erosion, drop * 0.5);
+                    self.heights[tile] -= taken;
+                    sediment += taken;
+                }
+
+                speed = f32::sqrt(speed * speed + drop * erosion.gravity);
+                water *= 1. - erosion.evaporation;
+                tile = next;
+            }
+            // whatever the droplet still carries settles where it stopped
+            self.heights[tile] += sediment;
+        }
+    }
+
+    /// Slides material off any slope steeper than the talus
+    /// onto the tiles below it until the terrain settles into even slopes
+    pub fn erode_thermal(&mut self, erosion: &ThermalErosion) {
+        let mut changes = vec![0.; self.len()];
+        for _ in 0..erosion.iterations {
+            changes.iter_mut().for_each(|change| *change = 0.);
+            for i in 0..self.len() {
+                let height = self.heights[i];
+                let excess: Vec<(usize, f32)> = self.neighbors(i)
+                    .map(|n| (n, height - self.heights[n] - erosion.talus))
+                    .filter(|(_, excess)| *excess > 0.)
+                    .collect();
+                let total: f32 = excess.iter().map(|(_, excess)| excess).sum();
+                let steepest = excess.iter().map(|(_, excess)| *excess).fold(0., f32::max);
+                if total <= 0. {
+                    continue;
+                }
+                // half the steepest excess levels that slope, so moving more would overshoot
+                let moved = steepest * 0.5 * erosion.rate;
+                changes[i] -= moved;
+                for (n, excess) in excess {
+                    changes[n] += moved * excess / total;
+                }
+            }
+            for (height, change) in self.heights.iter_mut().zip(changes.iter()) {
+                *height += change;
+            }
+        }
+    }
+}
+
+/// Settings for `HeightField::erode_hydraulic`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct HydraulicErosion {
+    pub droplets: u32,
+    /// most tiles a droplet flows across before it is gone
+    pub lifetime: u32,
+    /// sediment carried per height dropped at full speed and water
+    pub capacity: f32,
+    /// lets droplets keep carving on near flat ground
+    pub min_capacity: f32,
+    /// fraction of the missing capacity picked up each step
+    pub erosion: f32,
+    /// fraction of the extra sediment dropped each step
+    pub deposition: f32,
+    /// fraction of water lost each step
+    pub evaporation: f32,
+    /// speed gained per height dropped
+    pub gravity: f32
+}
+
+impl Default for HydraulicErosion {
+    fn default() -> Self {
+        Self {
+            droplets: 1500,
+            lifetime: 30,
+            capacity: 2.,
+            min_capacity: 0.01,
+            erosion: 0.3,
+            deposition: 0.3,
+            evaporation: 0.02,
+            gravity: 1.
+        }
+    }
+}
+
+/// Settings for `HeightField::erode_thermal`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ThermalErosion {
+    pub iterations: u32,
+    /// steepest height difference between neighbors that holds without sliding
+    pub talus: f32,
+    /// 0..1, how much of a slope's excess slides each iteration
+    pub rate: f32
+}
+
+impl Default for ThermalErosion {
+    fn default() -> Self {
+        Self { iterations: 20, talus: 3., rate: 0.5 }
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Generator ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+/// Heights from an eroded `HeightField`. Tiles outside it fall back to generator
+pub struct ErodedGenerator<G: HeightGenerator> {
+    generator: G,
+    field: HeightField
+}
+
+impl<G: HeightGenerator> ErodedGenerator<G> {
+    pub fn new(generator: G, field: HeightField) -> Self {
+        Self { generator, field }
+    }
+
+    /// Bakes generator over tiles and runs hydraulic erosion then thermal erosion on it
+    pub fn erode(
+        generator: G,
+        tiles: impl IntoIterator<Item = HexCoord>,
+        hydraulic: &HydraulicErosion,
+        thermal: &ThermalErosion,
+        seed: Option<u64>
+    ) -> Self {
+        let seed = seed.unwrap_or(rand::random());
+        let mut field = HeightField::bake(&generator, tiles);
+        field.erode_hydraulic(hydraulic, seed);
+        field.erode_thermal(thermal);
+        Self::new(generator, field)
+    }
+
+    // ~~~~~~~~~~~~~~ Prefabs ~~~~~~~~~~~~~~ //
+
+    /// Default erosion over the default map
+    pub fn weathered(generator: G, seed: Option<u64>) -> Self {
+        let tiles = HexCoord::ZERO.spiral(HEX_GRID_RADIUS);
+        Self::erode(generator, tiles, &HydraulicErosion::default(), &ThermalErosion::default(), seed)
+    }
+}
+
+impl<G: HeightGenerator> HeightGenerator for ErodedGenerator<G> {
+    fn generate_height(&self, coord: HexCoord) -> u32 {
+        match self.field.get(coord) {
+            Some(height) => height.round().max(0.) as u32,
+            None => self.generator.generate_height(coord)
+        }
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Tests ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::world_3d::hex::height_map::{FlatGenerator, PerlinGenerator};
+
+    fn bumpy_field() -> HeightField {
+        HeightField::bake(&PerlinGenerator::crags(Some(4)), HexCoord::ZERO.spiral(10))
+    }
+
+    /// Steepest height difference between any two neighboring tiles
+    fn steepest_slope(field: &HeightField) -> f32 {
+        (0..field.len())
+            .flat_map(|i| field.neighbors(i).map(move |n| (i, n)))
+            .map(|(i, n)| (field.heights[i] - field.heights[n]).abs())
+            .fold(0., f32::max)
+    }
+
+    #[test]
+    fn hydraulic_is_deterministic_per_seed() {
+        let erosion = HydraulicErosion { droplets: 300, ..HydraulicErosion::default() };
+        let erode = |seed| {
+            let mut field = bumpy_field();
+            field.erode_hydraulic(&erosion, seed);
+            field.heights
+        };
+        assert_eq!(erode(12), erode(12));
+        assert_ne!(erode(12), erode(13));
+        // and it did change something
+        assert_ne!(erode(12), bumpy_field().heights);
+    }
+
+    #[test]
+    fn thermal_settles_below_talus() {
+        // a spike much steeper than the talus
+        let mut field = HeightField::bake(&FlatGenerator::new(0), HexCoord::ZERO.spiral(6));
+        field.heights[field.index[&HexCoord::ZERO]] = 60.;
+        let total: f32 = field.heights.iter().sum();
+
+        let erosion = ThermalErosion { iterations: 400, talus: 3., rate: 0.5 };
+        field.erode_thermal(&erosion);
+        assert!(steepest_slope(&field) <= erosion.talus + 0.01, "slope {} left above talus", steepest_slope(&field));
+        // material only moves around
+        assert!((field.heights.iter().sum::<f32>() - total).abs() < 0.01);
+    }
+
+    #[test]
+    fn thermal_only_flattens() {
+        let mut field = bumpy_field();
+        let before = steepest_slope(&field);
+        field.erode_thermal(&ThermalErosion::default());
+        assert!(steepest_slope(&field) <= before);
+    }
+}
@@ -0,0 +1,236 @@
+//! Wrappers that shape terrain into islands surrounded by low ground.
+//! See: https://www.redblobgames.com/maps/terrain-from-noise/#islands
+use bevy::prelude::*;
+
+use crate::hex::HexCoord;
+use crate::plugins::world_3d::config::HEX_GRID_RADIUS;
+use super::{HeightGenerator, seeded_hash};
+use super::fractal::sample_point;
+use super::noise::PermutationTable;
+
+/// How height drops off toward the edge of an island
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Falloff {
+    /// fraction of the radius kept at full height before it starts dropping
+    pub plateau: f32,
+    /// height everything settles to past the shore
+    pub floor: u32
+}
+
+impl Falloff {
+    pub fn new(plateau: f32, floor: u32) -> Self {
+        assert!((0. ..1.).contains(&plateau), "falloff plateau must be in 0..1");
+        Self { plateau, floor }
+    }
+
+    /// 1 inside the plateau, easing down to 0 at fraction 1 of the radius
+    fn factor(&self, fraction: f32) -> f32 {
+        let t = ((fraction - self.plateau) / (1. - self.plateau)).clamp(0., 1.);
+        1. - t * t * (3. - 2. * t)
+    }
+
+    fn apply(&self, height: u32, factor: f32) -> u32 {
+        let floor = self.floor as f32;
+        (floor + (height as f32 - floor) * factor).max(0.) as u32
+    }
+}
+
+impl Default for Falloff {
+    fn default() -> Self {
+        Self { plateau: 0.4, floor: 0 }
+    }
+}
+
+/// Noise added to the distance from shore so coastlines wander instead of tracing a hexagon
+pub struct Coastline {
+    frequency: f32,
+    /// how far the shore can move in or out, as a fraction of the island's radius
+    roughness: f32,
+    table: PermutationTable
+}
+
+impl Coastline {
+    pub fn new(frequency: f32, roughness: f32, seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or(rand::random());
+        // its own table so the shore doesn't follow the terrain it is cutting
+        let coast_seed = seeded_hash(&seed.to_le_bytes(), seed, "Coastline");
+        Self { frequency, roughness, table: PermutationTable::new(coast_seed) }
+    }
+
+    fn distort(&self, coord: HexCoord, distance: f32, radius: f32) -> f32 {
+        let noise = self.table.simplex(sample_point(coord) * self.frequency);
+        distance + noise * self.roughness * radius
+    }
+
+    // ~~~~~~~~~~~~~~ Prefabs ~~~~~~~~~~~~~~ //
+
+    pub fn rugged(seed: Option<u64>) -> Self {
+        Self::new(0.12, 0.3, seed)
+    }
+
+    pub fn gentle(seed: Option<u64>) -> Self {
+        Self::new(0.05, 0.15, seed)
+    }
+}
+
+/// Fraction of the way from center to shore that coord sits at
+fn shore_fraction(coord: HexCoord, center: HexCoord, radius: f32, coastline: &Option<Coastline>) -> f32 {
+    let distance = coord.distance(center) as f32;
+    let distance = match coastline {
+        Some(coastline) => coastline.distort(coord, distance, radius),
+        None => distance
+    };
+    distance / radius.max(f32::EPSILON)
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Island ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+/// Sinks generator down to the floor with distance from center
+pub struct IslandGenerator<G: HeightGenerator> {
+    generator: G,
+    center: HexCoord,
+    /// tiles from center to shore
+    radius: f32,
+    falloff: Falloff,
+    coastline: Option<Coastline>
+}
+
+impl<G: HeightGenerator> IslandGenerator<G> {
+    pub fn new(generator: G, center: HexCoord, radius: u32) -> Self {
+        Self { generator, center, radius: radius as f32, falloff: default(), coastline: None }
+    }
+
+    pub fn with_falloff(mut self, falloff: Falloff) -> Self {
+        self.falloff = falloff;
+        self
+    }
+
+    pub fn with_coastline(mut self, coastline: Coastline) -> Self {
+        self.coastline = Some(coastline);
+        self
+    }
+
+    // ~~~~~~~~~~~~~~ Prefabs ~~~~~~~~~~~~~~ //
+
+    /// One island filling the default map
+    pub fn continent(generator: G, seed: Option<u64>) -> Self {
+        Self::new(generator, HexCoord::ZERO, HEX_GRID_RADIUS as u32)
+            .with_coastline(Coastline::gentle(seed))
+    }
+}
+
+impl<G: HeightGenerator> HeightGenerator for IslandGenerator<G> {
+    fn generate_height(&self, coord: HexCoord) -> u32 {
+        let fraction = shore_fraction(coord, self.center, self.radius, &self.coastline);
+        let factor = self.falloff.factor(fraction);
+        self.falloff.apply(self.generator.generate_height(coord), factor)
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Archipelago ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+/// Several islands raised out of the same generator. Where islands overlap the taller one wins
+pub struct ArchipelagoGenerator<G: HeightGenerator> {
+    generator: G,
+    /// (center, radius) of each island
+    islands: Vec<(HexCoord, f32)>,
+    falloff: Falloff,
+    coastline: Option<Coastline>
+}
+
+impl<G: HeightGenerator> ArchipelagoGenerator<G> {
+    /// islands: (center, radius) of each island
+    pub fn new(generator: G, islands: Vec<(HexCoord, u32)>) -> Self {
+        let islands = islands.into_iter().map(|(center, radius)| (center, radius as f32)).collect();
+        Self { generator, islands, falloff: default(), coastline: None }
+    }
+
+    /// count islands with centers somewhere within area tiles of HexCoord::ZERO.
+    /// Island radii are picked from min_radius..=max_radius
+    pub fn scattered(generator: G, count: u32, area: u32, min_radius: u32, max_radius: u32, seed: Option<u64>) -> Self {
+        assert!(min_radius <= max_radius, "archipelago min radius can't be above max radius");
+        let seed = seed.unwrap_or(rand::random());
+        // never empty, area 0 is just the center
+        let tiles: Vec<HexCoord> = HexCoord::ZERO.spiral(area as i32).collect();
+        let islands = (0..count)
+            .map(|i| {
+                let bytes = i.to_le_bytes();
+                let spot = seeded_hash(&bytes, seed, "Archipelago Center") as usize % tiles.len();
+                let spread = (max_radius - min_radius + 1) as u64;
+                let radius = min_radius + (seeded_hash(&bytes, seed, "Archipelago Radius") % spread) as u32;
+                (tiles[spot], radius)
+            })
+            .collect();
+        Self::new(generator, islands)
+    }
+
+    pub fn with_falloff(mut self, falloff: Falloff) -> Self {
+        self.falloff = falloff;
+        self
+    }
+
+    pub fn with_coastline(mut self, coastline: Coastline) -> Self {
+        self.coastline = Some(coastline);
+        self
+    }
+
+    // ~~~~~~~~~~~~~~ Prefabs ~~~~~~~~~~~~~~ //
+
+    /// A handful of mid sized islands across the default map
+    pub fn isles(generator: G, seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or(rand::random());
+        Self::scattered(generator, 5, HEX_GRID_RADIUS as u32 * 2 / 3, 4, 9, Some(seed))
+            .with_coastline(Coastline::rugged(Some(seed)))
+    }
+}
+
+impl<G: HeightGenerator> HeightGenerator for ArchipelagoGenerator<G> {
+    fn generate_height(&self, coord: HexCoord) -> u32 {
+        let factor = self.islands.iter()
+            .map(|(center, radius)| {
+                self.falloff.factor(shore_fraction(coord, *center, *radius, &self.coastline))
+            })
+            .fold(0., f32::max);
+        self.falloff.apply(self.generator.generate_height(coord), factor)
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Tests ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::world_3d::hex::height_map::FlatGenerator;
+
+    #[test]
+    fn no_islands_is_all_floor() {
+        let empty = ArchipelagoGenerator::new(FlatGenerator::new(10), Vec::new());
+        let none_scattered = ArchipelagoGenerator::scattered(FlatGenerator::new(10), 0, 5, 1, 3, Some(1));
+        assert!(none_scattered.islands.is_empty());
+        for coord in HexCoord::ZERO.spiral(6) {
+            assert_eq!(empty.generate_height(coord), 0);
+            assert_eq!(none_scattered.generate_height(coord), 0);
+        }
+    }
+
+    #[test]
+    fn zero_area_centers_every_island_on_one_tile() {
+        let archipelago = ArchipelagoGenerator::scattered(FlatGenerator::new(10), 4, 0, 2, 5, Some(1));
+        assert_eq!(archipelago.islands.len(), 4);
+        for (center, radius) in archipelago.islands.iter() {
+            assert_eq!(*center, HexCoord::ZERO);
+            assert!((2. ..=5.).contains(radius));
+        }
+        assert_eq!(archipelago.generate_height(HexCoord::ZERO), 10);
+    }
+
+    #[test]
+    fn scattered_is_deterministic_per_seed() {
+        let scatter = |seed| ArchipelagoGenerator::scattered(FlatGenerator::new(10), 6, 20, 2, 5, Some(seed)).islands;
+        assert_eq!(scatter(9), scatter(9));
+        assert_ne!(scatter(9), scatter(10));
+        for (center, _) in scatter(9) {
+            assert!(center.length() <= 20);
+        }
+    }
+}
@@ -0,0 +1,338 @@
+//! Height generators built from octaves of simplex noise.
+//! See: https://www.redblobgames.com/maps/terrain-from-noise/
+use bevy::prelude::*;
+
+use crate::hex::HexCoord;
+use super::{HeightGenerator, seeded_hash};
+use super::noise::PermutationTable;
+
+/// Noise that can be sampled anywhere in the plane, not just at tile centers
+pub trait NoiseField: Send + Sync + 'static {
+    /// value in 0..1
+    fn sample(&self, point: Vec2) -> f32;
+}
+
+/// Center of coord with the axial skew taken out, so noise stretches the same in every direction.
+/// Neighboring tiles are 1 apart
+pub fn sample_point(coord: HexCoord) -> Vec2 {
+    let q = coord.q() as f32;
+    let r = coord.r() as f32;
+    Vec2::new(q + r / 2., r * f32::sqrt(3.) / 2.)
+}
+
+/// How octaves of noise are layered on top of each other
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Fractal {
+    /// frequency of the first octave, in cycles per tile
+    pub frequency: f32,
+    pub octaves: u32,
+    /// each octave's frequency is this many times the last one's
+    pub lacunarity: f32,
+    /// each octave's amplitude is this many times the last one's
+    pub gain: f32
+}
+
+impl Fractal {
+    pub fn new(frequency: f32, octaves: u32) -> Self {
+        Self { frequency, octaves, ..default() }
+    }
+
+    /// (frequency, amplitude) of each octave
+    fn octaves(&self) -> impl Iterator<Item = (f32, f32)> + '_ {
+        (0..self.octaves).map(|i| {
+            (self.frequency * self.lacunarity.powi(i as i32), self.gain.powi(i as i32))
+        })
+    }
+
+    fn total_amplitude(&self) -> f32 {
+        self.octaves().map(|(_, amplitude)| amplitude).sum::<f32>().max(f32::EPSILON)
+    }
+}
+
+impl Default for Fractal {
+    fn default() -> Self {
+        Self { frequency: 0.05, octaves: 4, lacunarity: 2., gain: 0.5 }
+    }
+}
+
+fn height(field: &impl NoiseField, coord: HexCoord, magnitude: f32) -> u32 {
+    (field.sample(sample_point(coord)) * magnitude) as u32
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Simplex ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+/// Fractal OpenSimplex2 noise. Smooth rolling terrain
+pub struct SimplexGenerator {
+    fractal: Fractal,
+    magnitude: f32,
+    table: PermutationTable
+}
+
+impl SimplexGenerator {
+    /// magnitude: height of the highest peaks
+    pub fn new(fractal: Fractal, magnitude: f32, seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or(rand::random());
+        Self { fractal, magnitude, table: PermutationTable::new(seed) }
+    }
+
+    // ~~~~~~~~~~~~~~ Prefabs ~~~~~~~~~~~~~~ //
+
+    pub fn rolling_hills(seed: Option<u64>) -> Self {
+        Self::new(Fractal::new(0.04, 3), 20., seed)
+    }
+
+    pub fn plains(seed: Option<u64>) -> Self {
+        Self::new(Fractal::new(0.02, 2), 6., seed)
+    }
+
+    pub fn highlands(seed: Option<u64>) -> Self {
+        Self::new(Fractal::new(0.06, 5), 35., seed)
+    }
+}
+
+impl NoiseField for SimplexGenerator {
+    fn sample(&self, point: Vec2) -> f32 {
+        let sum: f32 = self.fractal.octaves()
+            .map(|(frequency, amplitude)| self.table.simplex(point * frequency) * amplitude)
+            .sum();
+        ((sum / self.fractal.total_amplitude()) * 0.5 + 0.5).clamp(0., 1.)
+    }
+}
+
+impl HeightGenerator for SimplexGenerator {
+    fn generate_height(&self, coord: HexCoord) -> u32 {
+        height(self, coord, self.magnitude)
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Ridged ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+/// Ridged multifractal noise. Sharp crests where the noise crosses zero,
+/// sharpest where the octaves before were already high. Makes mountain ranges.
+/// See: https://www.classes.cs.uchicago.edu/archive/2015/fall/23700-1/final-project/MusgraveTerrain00.pdf
+pub struct RidgedGenerator {
+    fractal: Fractal,
+    magnitude: f32,
+    table: PermutationTable
+}
+
+impl RidgedGenerator {
+    /// magnitude: height of the highest peaks
+    pub fn new(fractal: Fractal, magnitude: f32, seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or(rand::random());
+        Self { fractal, magnitude, table: PermutationTable::new(seed) }
+    }
+
+    // ~~~~~~~~~~~~~~ Prefabs ~~~~~~~~~~~~~~ //
+
+    pub fn mountains(seed: Option<u64>) -> Self {
+        Self::new(Fractal::new(0.03, 5), 45., seed)
+    }
+
+    pub fn ridges(seed: Option<u64>) -> Self {
+        Self::new(Fractal::new(0.06, 3), 25., seed)
+    }
+}
+
+impl NoiseField for RidgedGenerator {
+    fn sample(&self, point: Vec2) -> f32 {
+        let mut sum = 0.;
+        // octaves only add detail where the ones before them were high
+        let mut weight = 1.;
+        for (frequency, amplitude) in self.fractal.octaves() {
+            let ridge = 1. - self.table.simplex(point * frequency).abs();
+            let signal = ridge * ridge * weight;
+            weight = (signal * 2.).clamp(0., 1.);
+            sum += signal * amplitude;
+        }
+        (sum / self.fractal.total_amplitude()).clamp(0., 1.)
+    }
+}
+
+impl HeightGenerator for RidgedGenerator {
+    fn generate_height(&self, coord: HexCoord) -> u32 {
+        height(self, coord, self.magnitude)
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Billow ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+/// Folded noise. Round lumps with sharp creases between them
+pub struct BillowGenerator {
+    fractal: Fractal,
+    magnitude: f32,
+    table: PermutationTable
+}
+
+impl BillowGenerator {
+    /// magnitude: height of the highest peaks
+    pub fn new(fractal: Fractal, magnitude: f32, seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or(rand::random());
+        Self { fractal, magnitude, table: PermutationTable::new(seed) }
+    }
+
+    // ~~~~~~~~~~~~~~ Prefabs ~~~~~~~~~~~~~~ //
+
+    pub fn foothills(seed: Option<u64>) -> Self {
+        Self::new(Fractal::new(0.05, 4), 20., seed)
+    }
+
+    pub fn badlands(seed: Option<u64>) -> Self {
+        Self::new(Fractal::new(0.08, 3), 25., seed)
+    }
+}
+
+impl NoiseField for BillowGenerator {
+    fn sample(&self, point: Vec2) -> f32 {
+        let sum: f32 = self.fractal.octaves()
+            .map(|(frequency, amplitude)| self.table.simplex(point * frequency).abs() * amplitude)
+            .sum();
+        (sum / self.fractal.total_amplitude()).clamp(0., 1.)
+    }
+}
+
+impl HeightGenerator for BillowGenerator {
+    fn generate_height(&self, coord: HexCoord) -> u32 {
+        height(self, coord, self.magnitude)
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Domain Warp ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+/// Samples another noise field at points pushed around by more noise.
+/// Bends straight features into swirls and winding valleys.
+/// See: https://iquilezles.org/articles/warp/
+pub struct WarpedGenerator {
+    field: Box<dyn NoiseField>,
+    magnitude: f32,
+    /// how many tiles a point can be pushed
+    strength: f32,
+    warp: Fractal,
+    table: PermutationTable
+}
+
+impl WarpedGenerator {
+    /// magnitude: height of the highest peaks
+    pub fn new(field: impl NoiseField, magnitude: f32, strength: f32, warp: Fractal, seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or(rand::random());
+        // a different table from the field so the push doesn't line up with what's being pushed
+        let warp_seed = seeded_hash(&seed.to_le_bytes(), seed, "Domain Warp");
+        Self {
+            field: Box::new(field),
+            magnitude,
+            strength,
+            warp,
+            table: PermutationTable::new(warp_seed)
+        }
+    }
+
+    /// offset: shifts where in the noise the point lands
+    fn warp_noise(&self, point: Vec2, offset: Vec2) -> f32 {
+        let sum: f32 = self.warp.octaves()
+            .map(|(frequency, amplitude)| self.table.simplex(point * frequency + offset) * amplitude)
+            .sum();
+        sum / self.warp.total_amplitude()
+    }
+
+    // ~~~~~~~~~~~~~~ Prefabs ~~~~~~~~~~~~~~ //
+
+    pub fn winding_mountains(seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or(rand::random());
+        Self::new(RidgedGenerator::mountains(Some(seed)), 45., 12., Fractal::new(0.03, 2), Some(seed))
+    }
+
+    pub fn swirled_highlands(seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or(rand::random());
+        Self::new(SimplexGenerator::highlands(Some(seed)), 35., 8., Fractal::new(0.05, 3), Some(seed))
+    }
+}
+
+impl NoiseField for WarpedGenerator {
+    fn sample(&self, point: Vec2) -> f32 {
+        // far apart offsets give the two axes unrelated pushes
+        let push = Vec2::new(
+            self.warp_noise(point, Vec2::ZERO),
+            self.warp_noise(point, Vec2::new(5.2, 1.3))
+        );
+        self.field.sample(point + push * self.strength)
+    }
+}
+
+impl HeightGenerator for WarpedGenerator {
+    fn generate_height(&self, coord: HexCoord) -> u32 {
+        height(self, coord, self.magnitude)
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Tests ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// magnitude 1 so heights are just the sample floored
+    fn fields(seed: u64) -> Vec<(&'static str, Box<dyn NoiseField>)> {
+        let fractal = Fractal::new(0.1, 4);
+        vec![
+            ("simplex", Box::new(SimplexGenerator::new(fractal, 1., Some(seed)))),
+            ("ridged", Box::new(RidgedGenerator::new(fractal, 1., Some(seed)))),
+            ("billow", Box::new(BillowGenerator::new(fractal, 1., Some(seed)))),
+            ("warped", Box::new(WarpedGenerator::new(
+                SimplexGenerator::new(fractal, 1., Some(seed)), 1., 6., Fractal::new(0.05, 3), Some(seed)
+            ))),
+        ]
+    }
+
+    fn points() -> impl Iterator<Item = Vec2> {
+        HexCoord(0, 0).spiral(40).map(|coord| sample_point(coord) + Vec2::new(0.37, -0.21))
+    }
+
+    #[test]
+    fn same_seed_same_noise() {
+        for ((name, a), (_, b)) in fields(11).into_iter().zip(fields(11)) {
+            for point in points() {
+                assert_eq!(a.sample(point), b.sample(point), "{} at {:?}", name, point);
+            }
+        }
+    }
+
+    #[test]
+    fn seeds_give_different_noise() {
+        for ((name, a), (_, b)) in fields(11).into_iter().zip(fields(12)) {
+            let same = points().filter(|point| a.sample(*point) == b.sample(*point)).count();
+            assert!(same < 100, "{} matched at {} points", name, same);
+        }
+    }
+
+    #[test]
+    fn samples_stay_in_0_to_1() {
+        for seed in 0..4 {
+            for (name, field) in fields(seed) {
+                let (mut low, mut high) = (f32::INFINITY, f32::NEG_INFINITY);
+                for point in points() {
+                    let value = field.sample(point);
+                    assert!((0. ..=1.).contains(&value), "{} gave {} at {:?}", name, value, point);
+                    low = low.min(value);
+                    high = high.max(value);
+                }
+                // flat noise would pass the range check too
+                assert!(high - low > 0.2, "{} only spans {}..{}", name, low, high);
+            }
+        }
+    }
+
+    #[test]
+    fn heights_stay_under_magnitude() {
+        let generators: Vec<Box<dyn HeightGenerator>> = vec![
+            Box::new(SimplexGenerator::highlands(Some(3))),
+            Box::new(RidgedGenerator::mountains(Some(3))),
+            Box::new(BillowGenerator::badlands(Some(3))),
+            Box::new(WarpedGenerator::winding_mountains(Some(3))),
+        ];
+        for (generator, magnitude) in generators.iter().zip([35, 45, 25, 45]) {
+            for coord in HexCoord(0, 0).spiral(40) {
+                assert!(generator.generate_height(coord) <= magnitude);
+            }
+        }
+    }
+}
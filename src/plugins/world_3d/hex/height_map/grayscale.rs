@@ -0,0 +1,132 @@
+//! Height maps drawn as grayscale images. Black is height 0 and white is the tallest height.
+//!
+//! The center of the image sits on the center of the plane, with the image's x axis along
+//! the plane's first axis and its rows running down the plane's second axis
+use std::collections::HashSet;
+use std::path::Path;
+
+use bevy::prelude::*;
+use image::{ImageBuffer, ImageResult, Luma};
+
+use crate::hex::{HexCoord, HexLayout};
+use super::{HeightGenerator, HeightMap};
+
+/// Reads heights from an image. Each tile takes the pixel under its center
+pub struct ImageGenerator {
+    /// 16 bit gray values, row by row
+    pixels: Vec<u16>,
+    width: u32,
+    height: u32,
+    layout: HexLayout,
+    pixels_per_unit: f32,
+    /// height of a white pixel
+    max_height: u32
+}
+
+impl ImageGenerator {
+    /// pixels_per_unit: how many pixels cover one world unit of the plane
+    pub fn new(image: &image::DynamicImage, layout: HexLayout, pixels_per_unit: f32, max_height: u32) -> Self {
+        let gray = image.to_luma16();
+        Self {
+            width: gray.width(),
+            height: gray.height(),
+            pixels: gray.into_raw(),
+            layout,
+            pixels_per_unit,
+            max_height
+        }
+    }
+
+    pub fn open(path: impl AsRef<Path>, layout: HexLayout, pixels_per_unit: f32, max_height: u32) -> ImageResult<Self> {
+        let image = image::open(path)?;
+        Ok(Self::new(&image, layout, pixels_per_unit, max_height))
+    }
+}
+
+impl HeightGenerator for ImageGenerator {
+    fn generate_height(&self, coord: HexCoord) -> u32 {
+        let point = self.layout.project(self.layout.to_world(coord));
+        let pixel = point * self.pixels_per_unit + Vec2::new(self.width as f32, self.height as f32) / 2.;
+        let (x, y) = (pixel.x.floor(), pixel.y.floor());
+        // tiles off the image are flat
+        if x < 0. || y < 0. || x >= self.width as f32 || y >= self.height as f32 {
+            return 0;
+        }
+        let gray = self.pixels[y as usize * self.width as usize + x as usize];
+        (gray as f32 / u16::MAX as f32 * self.max_height as f32).round() as u32
+    }
+}
+
+/// Draws tiles of map into a 16 bit grayscale PNG that `ImageGenerator` can read back in.
+/// Heights are scaled so max_height is white, or the tallest tile if None.
+/// Space between tiles is black
+pub fn export_png(
+    map: &HeightMap,
+    tiles: impl IntoIterator<Item = HexCoord>,
+    layout: &HexLayout,
+    pixels_per_unit: f32,
+    max_height: Option<u32>,
+    path: impl AsRef<Path>
+) -> ImageResult<()> {
+    let tiles: HashSet<HexCoord> = tiles.into_iter().collect();
+    let max_height = match max_height {
+        Some(max_height) => max_height,
+        None => tiles.iter().map(|tile| map.get_height(*tile)).max().unwrap_or(1)
+    }.max(1);
+
+    // wide enough both ways from the center to fit the furthest tile's corners
+    let extent = tiles.iter()
+        .map(|tile| layout.project(layout.to_world(*tile)).abs())
+        .fold(Vec2::ZERO, Vec2::max) + Vec2::splat(layout.size);
+    let width = ((extent.x * 2. * pixels_per_unit).ceil() as u32).max(1);
+    let height = ((extent.y * 2. * pixels_per_unit).ceil() as u32).max(1);
+    let center = Vec2::new(width as f32, height as f32) / 2.;
+
+    let image = ImageBuffer::from_fn(width, height, |x, y| {
+        let pixel = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+        let coord = layout.from_pixel((pixel - center) / pixels_per_unit);
+        let gray = if tiles.contains(&coord) {
+            u32::min(map.get_height(coord), max_height) as f32 / max_height as f32
+        } else {
+            0.
+        };
+        Luma([(gray * u16::MAX as f32).round() as u16])
+    });
+    image.save(path)
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Tests ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::{HexOrientation, WorldPlane};
+    use crate::plugins::world_3d::hex::height_map::PerlinGenerator;
+
+    const PIXELS_PER_UNIT: f32 = 8.;
+    const MAX_HEIGHT: u32 = 60;
+
+    #[test]
+    fn png_round_trip() {
+        let dir = std::env::temp_dir().join(format!("magic_game_height_png_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("height_map.png");
+
+        let tiles: Vec<HexCoord> = HexCoord::ZERO.spiral(6).collect();
+        for orientation in [HexOrientation::Pointy, HexOrientation::Flat] {
+            let layout = HexLayout::new(orientation, 1.5, WorldPlane::XZ);
+            let map = HeightMap::new(PerlinGenerator::hills(Some(5)));
+            export_png(&map, tiles.iter().copied(), &layout, PIXELS_PER_UNIT, Some(MAX_HEIGHT), &path).unwrap();
+
+            let imported = ImageGenerator::open(&path, layout, PIXELS_PER_UNIT, MAX_HEIGHT).unwrap();
+            for tile in tiles.iter() {
+                // anything taller than max_height was clipped to white
+                let expected = map.get_height(*tile).min(MAX_HEIGHT);
+                assert_eq!(imported.generate_height(*tile), expected, "{:?} {:?}", orientation, tile);
+            }
+            // off the image is flat
+            assert_eq!(imported.generate_height(HexCoord(40, 0)), 0);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,209 @@
+//! Seeded gradient noise on an integer lattice.
+//! See: https://mrl.cs.nyu.edu/~perlin/noise/
+use std::f32::consts::FRAC_1_SQRT_2;
+
+use bevy::prelude::*;
+
+use super::seeded_hash;
+
+/// Unit vectors lattice points pick from by hash.
+///
+/// These only point into the first quadrant, same as the gradients of the original generator.
+/// That leans the noise positive and the `PerlinGenerator` prefabs were tuned around it
+const GRADIENTS: [Vec2; 8] = [
+    Vec2::new(0.9951847, 0.09801714),
+    Vec2::new(0.95694035, 0.29028466),
+    Vec2::new(0.8819213, 0.47139674),
+    Vec2::new(0.77301043, 0.6343933),
+    Vec2::new(0.6343933, 0.77301043),
+    Vec2::new(0.47139674, 0.8819213),
+    Vec2::new(0.29028466, 0.95694035),
+    Vec2::new(0.09801714, 0.9951847),
+];
+
+/// Unit vectors 15 degrees apart for simplex noise, which has no lean
+const SIMPLEX_GRADIENTS: [Vec2; 24] = [
+    Vec2::new(1., 0.),
+    Vec2::new(0.9659258, 0.25881904),
+    Vec2::new(0.8660254, 0.5),
+    Vec2::new(FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+    Vec2::new(0.5, 0.8660254),
+    Vec2::new(0.25881904, 0.9659258),
+    Vec2::new(0., 1.),
+    Vec2::new(-0.25881904, 0.9659258),
+    Vec2::new(-0.5, 0.8660254),
+    Vec2::new(-FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+    Vec2::new(-0.8660254, 0.5),
+    Vec2::new(-0.9659258, 0.25881904),
+    Vec2::new(-1., 0.),
+    Vec2::new(-0.9659258, -0.25881904),
+    Vec2::new(-0.8660254, -0.5),
+    Vec2::new(-FRAC_1_SQRT_2, -FRAC_1_SQRT_2),
+    Vec2::new(-0.5, -0.8660254),
+    Vec2::new(-0.25881904, -0.9659258),
+    Vec2::new(0., -1.),
+    Vec2::new(0.25881904, -0.9659258),
+    Vec2::new(0.5, -0.8660254),
+    Vec2::new(FRAC_1_SQRT_2, -FRAC_1_SQRT_2),
+    Vec2::new(0.8660254, -0.5),
+    Vec2::new(0.9659258, -0.25881904),
+];
+
+// OpenSimplex2 constants.
+// See: https://github.com/KdotJPG/OpenSimplex2
+/// Skews the square lattice onto the triangle lattice. (sqrt(3) - 1) / 2
+const SKEW: f32 = 0.366_025_4;
+/// Skews the triangle lattice back. (1 / sqrt(3) - 1) / 2
+const UNSKEW: f32 = -0.211_324_87;
+/// Radius squared of each lattice point's influence
+const RADIUS_SQUARED: f32 = 0.5;
+/// Scales the sum of the kernels to fill -1..1
+const SIMPLEX_NORMALIZE: f32 = 99.2;
+
+/// A shuffled table of 0..=255 used to hash lattice points.
+/// Built once per seed so sampling noise needs no hashing or allocation.
+/// Each 256 by 256 block of lattice points shifts its lookups by a few integer ops on
+/// the block and the seed, so the noise doesn't repeat from block to block.
+///
+/// The shuffle is driven by xxh3 rather than `rand` so a seed
+/// gives the same terrain on every platform and every version of our dependencies.
+/// Shifting by block changed the noise everywhere, so seeds saved before it give different maps
+#[derive(Debug, Clone)]
+pub struct PermutationTable {
+    perm: [u8; 256],
+    seed: u64
+}
+
+impl PermutationTable {
+    pub fn new(seed: u64) -> Self {
+        let mut perm = [0u8; 256];
+        for (i, value) in perm.iter_mut().enumerate() {
+            *value = i as u8;
+        }
+        // Fisher-Yates shuffle
+        for i in (1..perm.len()).rev() {
+            let roll = seeded_hash(&(i as u64).to_le_bytes(), seed, "Permutation Table");
+            let j = (roll % (i as u64 + 1)) as usize;
+            perm.swap(i, j);
+        }
+        Self { perm, seed }
+    }
+
+    /// Pseudo random byte for a lattice point
+    #[inline]
+    pub fn hash(&self, x: i32, y: i32) -> u8 {
+        let shift = self.block_shift(x >> 8, y >> 8);
+        let x = (x as u64).wrapping_add(shift) as usize & 255;
+        let y = (y as u64).wrapping_add(shift >> 8) as usize & 255;
+        self.perm[(self.perm[x] as usize + y) & 255]
+    }
+
+    /// Mixes a block of lattice points with the seed. Low bytes shift x then y.
+    /// Uses the splitmix64 finalizer, see: https://prng.di.unimi.it/splitmix64.c
+    #[inline]
+    fn block_shift(&self, block_x: i32, block_y: i32) -> u64 {
+        let block = (block_x as u32 as u64) | ((block_y as u32 as u64) << 32);
+        let mut z = self.seed.wrapping_add(block.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    #[inline]
+    fn gradient(&self, x: i32, y: i32) -> Vec2 {
+        GRADIENTS[(self.hash(x, y) & 7) as usize]
+    }
+
+    /// 2d perlin noise, within -1..1. Zero at every lattice point
+    pub fn perlin(&self, v: Vec2) -> f32 {
+        let floor = v.floor();
+        let (x, y) = (floor.x as i32, floor.y as i32);
+        let local = v - floor;
+
+        let g0 = self.gradient(x, y);
+        let g1 = self.gradient(x + 1, y);
+        let g2 = self.gradient(x, y + 1);
+        let g3 = self.gradient(x + 1, y + 1);
+
+        let d0 = g0.dot(local);
+        let d1 = g1.dot(local - Vec2::new(1., 0.));
+        let d2 = g2.dot(local - Vec2::new(0., 1.));
+        let d3 = g3.dot(local - Vec2::new(1., 1.));
+
+        let fade_x = fade(local.x);
+        let fade_y = fade(local.y);
+
+        let bottom = d0 + fade_x * (d1 - d0);
+        let top = d2 + fade_x * (d3 - d2);
+        bottom + fade_y * (top - bottom)
+    }
+
+    #[inline]
+    fn simplex_contribution(&self, x: i32, y: i32, offset: Vec2) -> f32 {
+        let a = RADIUS_SQUARED - offset.length_squared();
+        if a <= 0. {
+            return 0.;
+        }
+        let gradient = SIMPLEX_GRADIENTS[(self.hash(x, y) % 24) as usize];
+        (a * a) * (a * a) * gradient.dot(offset)
+    }
+
+    /// 2d OpenSimplex2 noise, within -1..1.
+    /// Smoother than perlin with fewer lines along the axes
+    pub fn simplex(&self, v: Vec2) -> f32 {
+        let skewed = v + SKEW * (v.x + v.y);
+        let base = skewed.floor();
+        let (x, y) = (base.x as i32, base.y as i32);
+        let local = skewed - base;
+
+        // offset from the base lattice point in unskewed space
+        let t = (local.x + local.y) * UNSKEW;
+        let d0 = local + t;
+
+        let mut value = self.simplex_contribution(x, y, d0);
+        value += self.simplex_contribution(x + 1, y + 1, d0 - (1. + 2. * UNSKEW));
+        // the third corner is whichever side of the diagonal the point is on
+        value += if d0.y > d0.x {
+            self.simplex_contribution(x, y + 1, d0 - Vec2::new(UNSKEW, UNSKEW + 1.))
+        } else {
+            self.simplex_contribution(x + 1, y, d0 - Vec2::new(UNSKEW + 1., UNSKEW))
+        };
+        value * SIMPLEX_NORMALIZE
+    }
+}
+
+/// Smooths interpolation between lattice points. 6t^5 - 15t^4 + 10t^3
+#[inline]
+pub fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6. - 15.) + 10.)
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Tests ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn noise_does_not_repeat_every_256_cells() {
+        let table = PermutationTable::new(7);
+        for period in [Vec2::new(256., 0.), Vec2::new(0., 256.), Vec2::new(-256., 512.)] {
+            let repeated = (0..64)
+                .map(|i| Vec2::new(i as f32 * 3.7 + 0.5, i as f32 * 1.3 + 0.25))
+                .filter(|point| table.perlin(*point) == table.perlin(*point + period))
+                .count();
+            assert!(repeated < 8, "{} of 64 points repeated after {:?}", repeated, period);
+        }
+    }
+
+    #[test]
+    fn same_seed_same_noise() {
+        let a = PermutationTable::new(7);
+        let b = PermutationTable::new(7);
+        for i in -40..40 {
+            let point = Vec2::new(i as f32 * 13.1, i as f32 * -9.7);
+            assert_eq!(a.perlin(point), b.perlin(point));
+            assert_eq!(a.simplex(point), b.simplex(point));
+        }
+    }
+}
@@ -0,0 +1,359 @@
+//! Rules and costs for moving pieces across the terrain
+use bevy::prelude::*;
+
+use crate::hex::{HexCoord, HexGraph, HexLayout, HexShape, HexWrap, pathfinding::reachable};
+use crate::plugins::world_3d::{
+    config::{
+        MOVE_COST,
+        CLIMB_COST,
+        DESCEND_COST,
+        MAX_CLIMB,
+        MAX_DROP,
+        SAFE_DROP,
+        FALL_DAMAGE_PER_LEVEL,
+        PLAYER_MOVE_BUDGET,
+    },
+    hex::{
+        HexTile,
+        TileMaterials,
+        height_map::HeightMap,
+    },
+    player::{
+        Player,
+        SelectedPlayer,
+    },
+};
+
+pub struct MovementPlugin;
+
+impl Plugin for MovementPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<MovementRules>()
+        .add_event::<FallDamageEvent>()
+        .add_system(show_reachable);
+    }
+}
+
+/// Tints every tile the selected player can move to.
+/// Tiles go back to normal when the player is deselected
+#[allow(clippy::too_many_arguments)]
+fn show_reachable(
+    selected: Res<SelectedPlayer>,
+    player_query: Query<&Transform, With<Player>>,
+    mut tile_query: Query<(&HexCoord, &mut Handle<StandardMaterial>), With<HexTile>>,
+    tile_materials: Res<TileMaterials>,
+    height_map: Res<HeightMap>,
+    layout: Res<HexLayout>,
+    shape: Res<HexShape>,
+    wrap: Res<HexWrap>,
+    rules: Res<MovementRules>
+) {
+    if !selected.is_changed() {
+        return;
+    }
+    let area = selected.0
+        .and_then(|player| player_query.get(player).ok())
+        .map(|transform| {
+            let start = wrap.canonical(layout.from_world(transform.translation));
+            let graph = TerrainGraph::new(&height_map, &shape, *wrap, &rules);
+            reachable(&graph, start, PLAYER_MOVE_BUDGET)
+        });
+
+    for (coord, mut material) in tile_query.iter_mut() {
+        let in_reach = matches!(&area, Some(area) if area.contains(*coord));
+        *material = if in_reach {
+            tile_materials.reachable.clone()
+        } else {
+            tile_materials.normal.clone()
+        };
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Rules ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+/// Fired when a piece is ordered down a drop deeper than `FallDamage::safe_drop`
+pub struct FallDamageEvent {
+    pub entity: Entity,
+    pub damage: u32
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FallDamage {
+    /// drops up to this many height levels don't hurt
+    pub safe_drop: u32,
+    /// damage for every level dropped past `safe_drop`
+    pub damage_per_level: u32
+}
+
+/// How height differences between tiles limit movement.
+/// Heights are the quantized values from `HeightMap::get_height`
+#[derive(Resource, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MovementRules {
+    /// cost of a step between tiles of the same height
+    pub move_cost: u32,
+    /// extra cost for each height level climbed
+    pub climb_cost: u32,
+    /// extra cost for each height level descended
+    pub descend_cost: u32,
+    /// tallest step up a piece can take. Anything taller is a cliff
+    pub max_climb: u32,
+    /// deepest step down a piece can take. Anything deeper is a cliff
+    pub max_drop: u32,
+    /// None if pieces can drop any allowed distance unharmed
+    pub fall_damage: Option<FallDamage>
+}
+
+impl Default for MovementRules {
+    fn default() -> Self {
+        Self {
+            move_cost: MOVE_COST,
+            climb_cost: CLIMB_COST,
+            descend_cost: DESCEND_COST,
+            max_climb: MAX_CLIMB,
+            max_drop: MAX_DROP,
+            fall_damage: Some(FallDamage {
+                safe_drop: SAFE_DROP,
+                damage_per_level: FALL_DAMAGE_PER_LEVEL
+            })
+        }
+    }
+}
+
+/// An allowed step between neighboring tiles
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub cost: u32,
+    pub fall_damage: u32
+}
+
+/// Why a move isn't allowed
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MoveError {
+    /// The tile isn't on the board
+    OffBoard(HexCoord),
+    /// The two tiles don't touch
+    NotAdjacent { from: HexCoord, to: HexCoord },
+    /// The step up is taller than `max_climb`
+    CliffUp { from: HexCoord, to: HexCoord, climb: u32 },
+    /// The step down is deeper than `max_drop`
+    CliffDown { from: HexCoord, to: HexCoord, drop: u32 }
+}
+
+impl MovementRules {
+    /// Checks a single step between neighbors of the given heights
+    pub fn step(&self, from_height: u32, to_height: u32) -> Option<Step> {
+        let climb = to_height.saturating_sub(from_height);
+        let drop = from_height.saturating_sub(to_height);
+        if climb > self.max_climb || drop > self.max_drop {
+            return None;
+        }
+        let fall_damage = match self.fall_damage {
+            Some(fall) => drop.saturating_sub(fall.safe_drop) * fall.damage_per_level,
+            None => 0
+        };
+        Some(Step {
+            cost: self.move_cost + climb * self.climb_cost + drop * self.descend_cost,
+            fall_damage
+        })
+    }
+
+    /// Checks every step of path against the rules.
+    /// On success returns the total cost and fall damage of the whole path
+    pub fn validate_path(
+        &self,
+        path: &[HexCoord],
+        map: &HeightMap,
+        shape: &HexShape,
+        wrap: HexWrap
+    ) -> Result<Step, MoveError> {
+        let mut total = Step { cost: 0, fall_damage: 0 };
+        for pair in path.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            if !shape.contains(wrap.canonical(to)) {
+                return Err(MoveError::OffBoard(to));
+            }
+            if wrap.distance(from, to) != 1 {
+                return Err(MoveError::NotAdjacent { from, to });
+            }
+            let from_height = map.get_height(from);
+            let to_height = map.get_height(to);
+            let step = match self.step(from_height, to_height) {
+                Some(step) => step,
+                None if to_height > from_height => {
+                    return Err(MoveError::CliffUp { from, to, climb: to_height - from_height })
+                },
+                None => {
+                    return Err(MoveError::CliffDown { from, to, drop: from_height - to_height })
+                }
+            };
+            total.cost += step.cost;
+            total.fall_damage += step.fall_damage;
+        }
+        Ok(total)
+    }
+
+    /// Fall damage taken walking path. Steps the rules don't allow are skipped,
+    /// so check the path with `validate_path` unless it came from a `TerrainGraph`
+    pub fn path_fall_damage(&self, path: &[HexCoord], map: &HeightMap) -> u32 {
+        path.windows(2)
+            .filter_map(|pair| self.step(map.get_height(pair[0]), map.get_height(pair[1])))
+            .map(|step| step.fall_damage)
+            .sum()
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Graph ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+/// The board as seen by a walking piece under some `MovementRules`.
+/// Cliffs and tiles off the board act as walls
+pub struct TerrainGraph<'a> {
+    map: &'a HeightMap,
+    shape: &'a HexShape,
+    wrap: HexWrap,
+    rules: &'a MovementRules
+}
+
+impl<'a> TerrainGraph<'a> {
+    pub fn new(map: &'a HeightMap, shape: &'a HexShape, wrap: HexWrap, rules: &'a MovementRules) -> Self {
+        Self { map, shape, wrap, rules }
+    }
+}
+
+impl<'a> HexGraph for TerrainGraph<'a> {
+    fn step_cost(&self, from: HexCoord, to: HexCoord) -> Option<u32> {
+        if !self.shape.contains(self.wrap.canonical(to)) {
+            return None;
+        }
+        let step = self.rules.step(self.map.get_height(from), self.map.get_height(to))?;
+        Some(step.cost)
+    }
+
+    fn neighbors(&self, coord: HexCoord) -> [HexCoord; 6] {
+        self.wrap.neighbors(coord)
+    }
+
+    fn distance(&self, a: HexCoord, b: HexCoord) -> u64 {
+        self.wrap.distance(a, b)
+    }
+
+    fn min_step_cost(&self) -> u32 {
+        self.rules.move_cost
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Tests ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::hex::pathfinding::find_path;
+    use crate::plugins::world_3d::hex::height_map::HeightGenerator;
+
+    const RULES: MovementRules = MovementRules {
+        move_cost: 2,
+        climb_cost: 2,
+        descend_cost: 1,
+        max_climb: 4,
+        max_drop: 6,
+        fall_damage: Some(FallDamage { safe_drop: 3, damage_per_level: 1 })
+    };
+
+    const BOARD: HexShape = HexShape::Hexagon { radius: 3 };
+
+    /// Heights picked by hand, 1 everywhere else
+    struct Heights(HashMap<HexCoord, u32>);
+
+    impl HeightGenerator for Heights {
+        fn generate_height(&self, coord: HexCoord) -> u32 {
+            self.0.get(&coord).copied().unwrap_or(1)
+        }
+    }
+
+    fn height_map(heights: &[(HexCoord, u32)]) -> HeightMap {
+        HeightMap::new(Heights(heights.iter().copied().collect()))
+    }
+
+    #[test]
+    fn climb_within_limit() {
+        assert_eq!(RULES.step(1, 5), Some(Step { cost: 2 + 4 * 2, fall_damage: 0 }));
+        assert_eq!(RULES.step(3, 3), Some(Step { cost: 2, fall_damage: 0 }));
+
+        let map = height_map(&[(HexCoord(1, 0), 5)]);
+        let path = [HexCoord(0, 0), HexCoord(1, 0)];
+        assert_eq!(RULES.validate_path(&path, &map, &BOARD, HexWrap::None), Ok(Step { cost: 10, fall_damage: 0 }));
+    }
+
+    #[test]
+    fn climb_over_limit() {
+        assert_eq!(RULES.step(1, 6), None);
+
+        let map = height_map(&[(HexCoord(1, 0), 6)]);
+        let path = [HexCoord(0, 0), HexCoord(1, 0)];
+        assert_eq!(
+            RULES.validate_path(&path, &map, &BOARD, HexWrap::None),
+            Err(MoveError::CliffUp { from: HexCoord(0, 0), to: HexCoord(1, 0), climb: 5 })
+        );
+        let graph = TerrainGraph::new(&map, &BOARD, HexWrap::None, &RULES);
+        assert_eq!(graph.step_cost(HexCoord(0, 0), HexCoord(1, 0)), None);
+    }
+
+    #[test]
+    fn drop_causes_fall_damage() {
+        // 5 levels down, 2 past the safe drop
+        assert_eq!(RULES.step(6, 1), Some(Step { cost: 2 + 5, fall_damage: 2 }));
+        assert_eq!(RULES.step(4, 1), Some(Step { cost: 2 + 3, fall_damage: 0 }));
+        assert_eq!(RULES.step(8, 1), None);
+        let harmless = MovementRules { fall_damage: None, ..RULES };
+        assert_eq!(harmless.step(6, 1), Some(Step { cost: 7, fall_damage: 0 }));
+
+        let map = height_map(&[(HexCoord(0, 0), 6), (HexCoord(2, 0), 8)]);
+        let path = [HexCoord(0, 0), HexCoord(1, 0)];
+        assert_eq!(RULES.validate_path(&path, &map, &BOARD, HexWrap::None), Ok(Step { cost: 7, fall_damage: 2 }));
+        assert_eq!(RULES.path_fall_damage(&path, &map), 2);
+        assert_eq!(
+            RULES.validate_path(&[HexCoord(2, 0), HexCoord(3, 0)], &map, &BOARD, HexWrap::None),
+            Err(MoveError::CliffDown { from: HexCoord(2, 0), to: HexCoord(3, 0), drop: 7 })
+        );
+    }
+
+    #[test]
+    fn off_board_and_non_adjacent() {
+        let map = height_map(&[]);
+        assert_eq!(
+            RULES.validate_path(&[HexCoord(3, 0), HexCoord(4, 0)], &map, &BOARD, HexWrap::None),
+            Err(MoveError::OffBoard(HexCoord(4, 0)))
+        );
+        assert_eq!(
+            RULES.validate_path(&[HexCoord(0, 0), HexCoord(2, 0)], &map, &BOARD, HexWrap::None),
+            Err(MoveError::NotAdjacent { from: HexCoord(0, 0), to: HexCoord(2, 0) })
+        );
+        let graph = TerrainGraph::new(&map, &BOARD, HexWrap::None, &RULES);
+        assert_eq!(graph.step_cost(HexCoord(3, 0), HexCoord(4, 0)), None);
+    }
+
+    #[test]
+    fn validate_path_agrees_with_find_path() {
+        // bumpy enough to have cliffs the paths must go around
+        let heights: Vec<(HexCoord, u32)> = BOARD.tiles().into_iter()
+            .map(|coord| (coord, 1 + (coord.0 * 3 + coord.1 * 5).rem_euclid(7) as u32))
+            .collect();
+        let map = height_map(&heights);
+        let graph = TerrainGraph::new(&map, &BOARD, HexWrap::None, &RULES);
+        let start = HexCoord(0, 0);
+        let mut found = 0;
+        for goal in BOARD.tiles() {
+            let path = match find_path(&graph, start, goal) {
+                Some(path) => path,
+                None => continue
+            };
+            found += 1;
+            let summary = RULES.validate_path(&path.tiles, &map, &BOARD, HexWrap::None).unwrap();
+            assert_eq!(summary.cost, path.cost, "to {:?}", goal);
+            assert_eq!(summary.fall_damage, RULES.path_fall_damage(&path.tiles, &map));
+        }
+        assert!(found > 1);
+    }
+}
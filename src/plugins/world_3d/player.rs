@@ -9,37 +9,59 @@ use bevy_mod_picking::{
 
 use crate::plugins::world_3d::{
     transformation::{
-        Transformation,
+        GameClock,
         HexPathingLine,
+        TransformationCommandsExt,
     },
     config::{
+        PLAYER_MOVE_BUDGET,
         PLAYER_SCALE,
         PLAYER_SPEED,
     },
     hex::{
-        HexCoord,
         HexTile,
         height_map::HeightMap,
-    }
+    },
+    movement::{
+        FallDamageEvent,
+        MovementRules,
+        TerrainGraph,
+    },
 };
+use crate::hex::{HexCoord, HexLayout, HexShape, HexWrap, pathfinding::reachable};
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app
+        .init_resource::<SelectedPlayer>()
         .add_startup_system(spawn_player)
         .add_system(player_mover)
+        // after this frame's moves have been applied so nothing drags the piece back
+        .add_system_to_stage(CoreStage::PostUpdate, relayout_players)
         ;
     }
 }
 
+/// The player piece currently selected, if any
+#[derive(Resource, Default)]
+pub struct SelectedPlayer(pub Option<Entity>);
+
+#[allow(clippy::too_many_arguments)]
 fn player_mover(
     mut commands: Commands,
+    mut selected: ResMut<SelectedPlayer>,
     mut events: EventReader<PickingEvent>,
     player_query: Query<(Entity, &Transform, &Children), With<Player>>,
     tile_query: Query<&HexCoord, With<HexTile>>,
-    height_map: Res<HeightMap>
+    height_map: Res<HeightMap>,
+    layout: Res<HexLayout>,
+    wrap: Res<HexWrap>,
+    shape: Res<HexShape>,
+    rules: Res<MovementRules>,
+    clock: Res<GameClock>,
+    mut fall_events: EventWriter<FallDamageEvent>
 ) {
     let mut player_to_move: Option<Entity> = None;
     let mut move_to: Option<HexCoord> = None;
@@ -52,13 +74,21 @@ fn player_mover(
                     if let Ok(tile_coord) = tile_query.get(*e) {
                         move_to = Some(tile_coord.clone());
                     }
+                    for (player_e, _, children) in player_query.iter() {
+                        if children.contains(e) {
+                            selected.0 = Some(player_e);
+                        }
+                    }
                 },
                 SelectionEvent::JustDeselected(picked_entity) => {
                     // pickable bundle is on the child entity of player so check children
                     // TODO: just select tile and then check if any player is on tile
                     for (e, _, children) in player_query.iter() {
                         if children.contains(picked_entity) {
-                            player_to_move = Some(e.clone())
+                            player_to_move = Some(e.clone());
+                            if selected.0 == Some(e) {
+                                selected.0 = None;
+                            }
                         }
                     }
                 }
@@ -71,13 +101,30 @@ fn player_mover(
     if let (Some(tile_coord), Some(player_e)) = (move_to, player_to_move) {
         let player = player_query.get(player_e);
         if let Ok((entity, transform, _)) = player {
-            let animation: Transformation = HexPathingLine::new(
-                HexCoord::from_world(transform.translation),
-                tile_coord,
+            let start = layout.from_world(transform.translation);
+            let graph = TerrainGraph::new(&height_map, &shape, *wrap, &rules);
+            // same budget the tinted tiles are drawn with. Out of reach so stay put
+            let path = match reachable(&graph, wrap.canonical(start), PLAYER_MOVE_BUDGET).path_to(tile_coord) {
+                Some(path) => path,
+                None => return
+            };
+            // paths through the graph only take allowed steps so there is nothing left to validate
+            let fall_damage = rules.path_fall_damage(&path.tiles, &height_map);
+            if fall_damage > 0 {
+                fall_events.send(FallDamageEvent { entity, damage: fall_damage });
+            }
+            // on wrapping maps the path may cross the seam so walk across it instead of jumping
+            let tiles = wrap.unwrap_path(start, &path.tiles);
+            // start from wherever the piece is now in case it is still moving
+            let animation = HexPathingLine::from_position(
+                transform.translation,
+                &tiles,
                 PLAYER_SPEED,
-                &height_map
-            ).into();
-            commands.entity(entity).insert(animation);
+                clock.now(),
+                &height_map,
+                &layout
+            );
+            commands.entity(entity).start_transformation(animation);
         }
     }
 }
@@ -85,16 +132,41 @@ fn player_mover(
 #[derive(Component, Inspectable)]
 pub struct Player;
 
+/// Puts pieces back on the tile they stood on when the layout changes, stopping any move in progress
+fn relayout_players(
+    mut commands: Commands,
+    layout: Res<HexLayout>,
+    mut previous: Local<Option<HexLayout>>,
+    height_map: Res<HeightMap>,
+    mut query: Query<(Entity, &mut Transform), With<Player>>
+) {
+    if !layout.is_changed() {
+        return;
+    }
+    let previous = match previous.replace(*layout) {
+        Some(previous) if previous != *layout => previous,
+        _ => return
+    };
+    for (entity, mut transform) in query.iter_mut() {
+        let coord = previous.from_world(transform.translation);
+        transform.translation = height_map.world_position(coord, &layout);
+        commands.entity(entity).cancel_transformation();
+    }
+}
+
 fn spawn_player(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    height_map: Res<HeightMap>
+    height_map: Res<HeightMap>,
+    layout: Res<HexLayout>,
+    shape: Res<HexShape>
 ) {
-    let material = materials.add(Color::rgb(1., 0.2, 0.2).into());
+    // a material per mesh so tweening one never recolors another
+    let color = Color::rgb(1., 0.2, 0.2);
 
-    let coord = HexCoord(0,0);
-    let position = coord.to_world(Some(&height_map));
+    let coord = shape.center().unwrap_or(HexCoord(0,0));
+    let position = height_map.world_position(coord, &layout);
     let scale = Vec3::splat(PLAYER_SCALE);
     commands
         .spawn(PbrBundle {
@@ -109,7 +181,7 @@ fn spawn_player(
         .with_children(|parent| {
             parent.spawn(PbrBundle {
                 mesh: asset_server.load("meshes/pieces.glb#Mesh0/Primitive0"),
-                material: material.clone(),
+                material: materials.add(color.into()),
                 transform: Transform {
                     translation: Vec3::new(- PLAYER_SCALE, - PLAYER_SCALE, - 10.*PLAYER_SCALE),
                     scale,
@@ -120,7 +192,7 @@ fn spawn_player(
             .insert(PickableBundle::default());
             parent.spawn(PbrBundle {
                 mesh: asset_server.load("meshes/pieces.glb#Mesh1/Primitive0"),
-                material,
+                material: materials.add(color.into()),
                 transform: Transform {
                     translation: Vec3::new(- PLAYER_SCALE, - PLAYER_SCALE, - 10.*PLAYER_SCALE),
                     scale,
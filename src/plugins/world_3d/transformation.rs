@@ -1,21 +1,87 @@
 //! Plugin for handling entity movement
+pub mod combinators;
+pub mod easing;
+pub mod queue;
+pub mod spline;
+pub mod tweens;
+
+use bevy::ecs::system::{Command, EntityCommands};
 use bevy::prelude::*;
-use std::time::{SystemTime, UNIX_EPOCH};
 
+pub use combinators::{Delay, Parallel, PingPong, Repeat, Reverse};
+pub use easing::{Eased, Easing};
+pub use queue::TransformationQueue;
+pub use spline::{SplineMovement, SplinePath};
+pub use tweens::{ColorTween, RotateTo, ScaleTo};
+
+use crate::hex::{HexCoord, HexLayout};
 use crate::plugins::world_3d::{
-    hex::{
-        HexCoord,
-        height_map::HeightMap,
+    config::{
+        HOP_HEIGHT,
+        MAX_HOP_HEIGHT,
+        CLIMB_SPEED_FACTOR,
     },
-    config::HEX_SMALL_DIAMETER,
+    hex::height_map::HeightMap,
 };
 
-// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Utils ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Clock ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+/// The timeline every `Transformer` runs on, in ms since the app started.
+///
+/// Follows bevy's `Time` each frame, scaled by `time_scale` and frozen while paused.
+/// `advance` moves it by hand, so tests can step animations without a real clock
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct GameClock {
+    elapsed: f64,
+    time_scale: f64,
+    paused: bool
+}
+
+impl Default for GameClock {
+    fn default() -> Self {
+        Self { elapsed: 0., time_scale: 1., paused: false }
+    }
+}
+
+impl GameClock {
+    /// game time in ms
+    pub fn now(&self) -> f64 {
+        self.elapsed
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn time_scale(&self) -> f64 {
+        self.time_scale
+    }
+
+    /// 2.0 plays animations twice as fast, 0.5 at half speed
+    pub fn set_time_scale(&mut self, time_scale: f64) {
+        assert!(time_scale >= 0., "time scale can't be negative");
+        self.time_scale = time_scale;
+    }
 
-#[inline]
-/// unix time in ms
-pub fn now() -> f64 {
-    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as f64
+    /// Moves game time forward by ms, ignoring pause and time scale
+    pub fn advance(&mut self, ms: f64) {
+        self.elapsed += ms;
+    }
+
+    /// Moves game time forward by a frame of real time
+    pub fn tick(&mut self, real_ms: f64) {
+        if !self.paused {
+            self.elapsed += real_ms * self.time_scale;
+        }
+    }
 }
 
 // ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ System ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
@@ -24,23 +90,151 @@ pub struct TransformationPlugin;
 impl Plugin for TransformationPlugin {
     fn build(&self, app: &mut App) {
         app
-        .add_system(transformation_driver);
+        .init_resource::<GameClock>()
+        .add_event::<TransformationStarted>()
+        .add_event::<TransformationFinished>()
+        .add_event::<TransformationCancelled>()
+        .add_system_to_stage(CoreStage::PreUpdate, tick_game_clock)
+        .add_system(queue::transformation_queue_driver.before(transformation_driver))
+        .add_system(transformation_driver)
+        .add_system(tweens::own_tweened_materials.before(tweens::color_tween_driver))
+        .add_system(tweens::color_tween_driver);
     }
 }
 
+fn tick_game_clock(time: Res<Time>, mut clock: ResMut<GameClock>) {
+    clock.tick(time.delta_seconds_f64() * 1000.);
+}
+
 fn transformation_driver(
     mut commands: Commands,
-    mut query: Query<(Entity, &mut Transform, &mut Transformation)>
+    clock: Res<GameClock>,
+    mut query: Query<(Entity, &mut Transform, &Transformation, ChangeTrackers<Transformation>)>,
+    mut started_events: EventWriter<TransformationStarted>
 ) {
-    let curr_time = now();
-    for (entity, mut transform, transformation) in query.iter_mut() {
+    let curr_time = clock.now();
+    for (entity, mut transform, transformation, tracker) in query.iter_mut() {
+        if tracker.is_added() {
+            started_events.send(TransformationStarted { entity });
+        }
         transformation.update(&mut transform, curr_time);
         if transformation.is_finished(curr_time) {
-            commands.entity(entity).remove::<Transformation>();
+            commands.add(FinishTransformation { entity });
         }
     }
 }
 
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Events ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+/// Sent the first frame a `Transformation` runs on entity
+pub struct TransformationStarted {
+    pub entity: Entity
+}
+
+/// Sent when a `Transformation` reaches its end and is removed from entity
+pub struct TransformationFinished {
+    pub entity: Entity
+}
+
+/// Sent when a `Transformation` is stopped early or replaced by another.
+/// The entity is left wherever it was last moved to
+pub struct TransformationCancelled {
+    pub entity: Entity
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Commands ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+/// Starting and stopping transformations so the right events get sent.
+/// Inserting a `Transformation` directly over a running one replaces it without a `TransformationCancelled`
+pub trait TransformationCommandsExt {
+    /// Runs transformation on this entity, cancelling any that is already running.
+    /// Build it from the entity's current transform so it doesn't snap
+    fn start_transformation(&mut self, transformation: impl Into<Transformation>) -> &mut Self;
+
+    /// Stops any running transformation, leaving the entity where it is
+    fn cancel_transformation(&mut self) -> &mut Self;
+}
+
+impl<'w, 's, 'a> TransformationCommandsExt for EntityCommands<'w, 's, 'a> {
+    fn start_transformation(&mut self, transformation: impl Into<Transformation>) -> &mut Self {
+        let entity = self.id();
+        self.commands().add(StartTransformation { entity, transformation: transformation.into() });
+        self
+    }
+
+    fn cancel_transformation(&mut self) -> &mut Self {
+        let entity = self.id();
+        self.commands().add(CancelTransformation { entity });
+        self
+    }
+}
+
+struct StartTransformation {
+    entity: Entity,
+    transformation: Transformation
+}
+
+impl Command for StartTransformation {
+    fn write(self, world: &mut World) {
+        cancel(world, self.entity);
+        if let Some(mut entity) = world.get_entity_mut(self.entity) {
+            entity.insert(self.transformation);
+        }
+    }
+}
+
+struct CancelTransformation {
+    entity: Entity
+}
+
+impl Command for CancelTransformation {
+    fn write(self, world: &mut World) {
+        cancel(world, self.entity);
+    }
+}
+
+/// Removes a transformation the driver saw finish. Another command may have
+/// replaced it by the time this runs, so only removes it if it is still finished.
+/// A replacement can already be finished when it is inserted, so its end state is applied first
+struct FinishTransformation {
+    entity: Entity
+}
+
+impl Command for FinishTransformation {
+    fn write(self, world: &mut World) {
+        let curr_time = world.resource::<GameClock>().now();
+        let finished = match world.get::<Transformation>(self.entity) {
+            Some(transformation) => transformation.is_finished(curr_time),
+            None => false
+        };
+        if finished {
+            let mut entity = world.entity_mut(self.entity);
+            if let Some(transformation) = entity.remove::<Transformation>() {
+                if let Some(mut transform) = entity.get_mut::<Transform>() {
+                    transformation.update(&mut transform, curr_time);
+                }
+            }
+            world.send_event(TransformationFinished { entity: self.entity });
+        }
+    }
+}
+
+/// Removes entity's transformation. One that had already reached its end counts as finished rather than cancelled
+fn cancel(world: &mut World, entity: Entity) {
+    let curr_time = world.resource::<GameClock>().now();
+    let removed = match world.get_entity_mut(entity) {
+        Some(mut entity) => entity.remove::<Transformation>(),
+        None => None
+    };
+    match removed {
+        Some(transformation) if transformation.is_finished(curr_time) => {
+            world.send_event(TransformationFinished { entity });
+        },
+        Some(_) => world.send_event(TransformationCancelled { entity }),
+        None => {}
+    }
+}
+
 // ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Wrapper Struct ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
 
 
@@ -64,6 +258,19 @@ impl Transformation {
         self.transformer.is_finished(curr_time)
     }
 
+    pub fn start_time(&self) -> f64 {
+        self.transformer.start_time()
+    }
+
+    pub fn end_time(&self) -> f64 {
+        self.transformer.end_time()
+    }
+
+    /// Shifts the whole transformation to begin at start_time
+    pub fn starting_at(self, start_time: f64) -> Self {
+        Self::new(Delay::starting_at(self.transformer, start_time))
+    }
+
 }
 
 impl<T: Transformer> From<T> for Transformation {
@@ -77,17 +284,73 @@ impl<T: Transformer> From<T> for Transformation {
 pub trait Transformer: Send + Sync + 'static {
     /// Edits a transform based on a time.
     /// 
-    /// time: unix_time in ms
+    /// time: game time in ms. See `GameClock`
     /// 
     /// If a time that is passed in is after the ending time of this transformer then
     /// the transformer should update the transformer to min(time, transformer.end_time)
     /// rather than going past its desired ending position
     fn update(&self, transform: &mut Transform, time: f64);
-    fn is_finished(&self, time: f64) -> bool;
+
+    /// game time in ms at which this transformer starts moving
+    fn start_time(&self) -> f64;
+
+    /// game time in ms at which this transformer reaches its final state
+    fn end_time(&self) -> f64;
+
+    fn duration(&self) -> f64 {
+        self.end_time() - self.start_time()
+    }
+
+    fn is_finished(&self, time: f64) -> bool {
+        time >= self.end_time()
+    }
+
+    /// Plays this transformer along an easing curve instead of at a constant rate
+    fn eased(self, easing: Easing) -> Eased<Self> where Self: Sized {
+        Eased::new(self, easing)
+    }
+
+    fn delayed(self, delay: f64) -> Delay<Self> where Self: Sized {
+        Delay::new(self, delay)
+    }
+
+    fn repeated(self, times: u32) -> Repeat<Self> where Self: Sized {
+        Repeat::new(self, times)
+    }
+
+    fn looped(self) -> Repeat<Self> where Self: Sized {
+        Repeat::forever(self)
+    }
+
+    fn ping_pong(self, times: u32) -> PingPong<Self> where Self: Sized {
+        PingPong::new(self, times)
+    }
+
+    fn reversed(self) -> Reverse<Self> where Self: Sized {
+        Reverse::new(self)
+    }
 }
 
 // ~~~~~~~~~~~~~~~~~~~~~~~~~~~ Trait Implementors ~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
 
+impl Transformer for Box<dyn Transformer> {
+    fn update(&self, transform: &mut Transform, time: f64) {
+        self.as_ref().update(transform, time)
+    }
+
+    fn start_time(&self) -> f64 {
+        self.as_ref().start_time()
+    }
+
+    fn end_time(&self) -> f64 {
+        self.as_ref().end_time()
+    }
+
+    fn is_finished(&self, time: f64) -> bool {
+        self.as_ref().is_finished(time)
+    }
+}
+
 #[derive(Debug)]
 pub struct LinearMovement {
     start_time: f64,
@@ -99,7 +362,7 @@ pub struct LinearMovement {
 impl LinearMovement {
     pub fn new(start_pos: Vec3, end_pos: Vec3, speed: f32, start_time: f64) -> Self {
         let path = end_pos - start_pos;
-        let dir = path.normalize();
+        let dir = path.normalize_or_zero();
         let velocity = dir * speed;
         let duration = (path.length() / speed) as f64;
         let end_time = duration + start_time;
@@ -115,14 +378,18 @@ impl LinearMovement {
 impl Transformer for LinearMovement {
 
     fn update(&self, transform: &mut Transform, time: f64) {
-        let time = f64::min(time, self.end_time);
+        let time = f64::max(self.start_time, f64::min(time, self.end_time));
         let dur = time - self.start_time;
         let curr_pos = self.start_pos + self.velocity * dur as f32;
         transform.translation = curr_pos;
     }
 
-    fn is_finished(&self, time: f64) -> bool {
-        time >= self.end_time
+    fn start_time(&self) -> f64 {
+        self.start_time
+    }
+
+    fn end_time(&self) -> f64 {
+        self.end_time
     }
 }
 
@@ -139,6 +406,16 @@ impl TransformerSeries {
     pub fn push(&mut self, transformer: impl Transformer) {
         self.transformers.push(Box::new(transformer))
     }
+
+    /// Pushes transformer shifted to start right when the series currently ends
+    pub fn then(&mut self, transformer: impl Transformer) {
+        if self.transformers.is_empty() {
+            self.push(transformer);
+        } else {
+            let start_time = self.end_time();
+            self.push(Delay::starting_at(transformer, start_time));
+        }
+    }
 }
 
 impl Transformer for TransformerSeries {
@@ -160,40 +437,222 @@ impl Transformer for TransformerSeries {
         }
     }
 
-    fn is_finished(&self, time: f64) -> bool {
+    fn start_time(&self) -> f64 {
+        match self.transformers.first() {
+            Some(transformer) => transformer.start_time(),
+            None => f64::NEG_INFINITY
+        }
+    }
+
+    fn end_time(&self) -> f64 {
         match self.transformers.last() {
-            Some(transformer) => transformer.is_finished(time),
-            None => true
+            Some(transformer) => transformer.end_time(),
+            None => f64::NEG_INFINITY
+        }
+    }
+}
+
+/// Jumps along a curve from one position to another,
+/// peaking `HOP_HEIGHT` above the higher of the two
+/// See: https://en.wikipedia.org/wiki/B%C3%A9zier_curve#Quadratic_curves
+#[derive(Debug)]
+pub struct HopMovement {
+    start_time: f64,
+    end_time: f64,
+    start_pos: Vec3,
+    control: Vec3,
+    end_pos: Vec3
+}
+
+impl HopMovement {
+    pub fn new(start_pos: Vec3, end_pos: Vec3, speed: f32, start_time: f64) -> Self {
+        // heights relative to the start. Solving y'(t) = 0 for the control height
+        // that makes the top of the curve land exactly on peak
+        let peak = f32::max(0., end_pos.y - start_pos.y) + HOP_HEIGHT;
+        let rise = end_pos.y - start_pos.y;
+        let mut control = (start_pos + end_pos) / 2.;
+        control.y = start_pos.y + peak + (peak * peak - peak * rise).sqrt();
+        // average of the chord and the control polygon is close to the curve length
+        let length = (
+            start_pos.distance(end_pos)
+            + start_pos.distance(control)
+            + control.distance(end_pos)
+        ) / 2.;
+        let end_time = start_time + (length / speed) as f64;
+        Self { start_time, end_time, start_pos, control, end_pos }
+    }
+}
+
+impl Transformer for HopMovement {
+    fn update(&self, transform: &mut Transform, time: f64) {
+        let duration = self.end_time - self.start_time;
+        let t = if duration > 0. {
+            ((time - self.start_time) / duration).clamp(0., 1.) as f32
+        } else {
+            1.
+        };
+        // the curve only passes near end_pos at t = 1 after rounding, so land on it exactly
+        if t >= 1. {
+            transform.translation = self.end_pos;
+            return;
+        }
+        let start_mid = self.start_pos.lerp(self.control, t);
+        let mid_end = self.control.lerp(self.end_pos, t);
+        transform.translation = start_mid.lerp(mid_end, t);
+    }
+
+    fn start_time(&self) -> f64 {
+        self.start_time
+    }
+
+    fn end_time(&self) -> f64 {
+        self.end_time
+    }
+}
+
+/// Walks to the edge of the tile, climbs straight up or down
+/// at `CLIMB_SPEED_FACTOR` of normal speed, then walks onto the next tile.
+/// For height differences too big to hop
+pub struct ClimbMovement {
+    transformers: TransformerSeries
+}
+
+impl ClimbMovement {
+    pub fn new(start_pos: Vec3, end_pos: Vec3, speed: f32, start_time: f64) -> Self {
+        // stop short of the wall when going up and step out past the ledge when going down
+        let edge_fraction = if end_pos.y > start_pos.y {0.4} else {0.6};
+        let mut edge_start = start_pos.lerp(end_pos, edge_fraction);
+        edge_start.y = start_pos.y;
+        let mut edge_end = edge_start;
+        edge_end.y = end_pos.y;
+
+        let mut transformers = TransformerSeries::new();
+        let walk_to_edge = LinearMovement::new(start_pos, edge_start, speed, start_time);
+        let climb = LinearMovement::new(edge_start, edge_end, speed * CLIMB_SPEED_FACTOR, walk_to_edge.end_time());
+        let walk_on = LinearMovement::new(edge_end, end_pos, speed, climb.end_time());
+        transformers.push(walk_to_edge);
+        transformers.push(climb);
+        transformers.push(walk_on);
+        Self { transformers }
+    }
+}
+
+impl Transformer for ClimbMovement {
+    fn update(&self, transform: &mut Transform, time: f64) {
+        self.transformers.update(transform, time)
+    }
+
+    fn start_time(&self) -> f64 {
+        self.transformers.start_time()
+    }
+
+    fn end_time(&self) -> f64 {
+        self.transformers.end_time()
+    }
+}
+
+/// A single step between two neighboring tiles.
+/// Picks how to move based on the height difference so pieces don't clip through tiles
+pub enum StepMovement {
+    Walk(LinearMovement),
+    Hop(HopMovement),
+    Climb(ClimbMovement)
+}
+
+impl StepMovement {
+    pub fn new(start_pos: Vec3, end_pos: Vec3, speed: f32, start_time: f64) -> Self {
+        let height_diff = (end_pos.y - start_pos.y).abs();
+        if height_diff < f32::EPSILON {
+            StepMovement::Walk(LinearMovement::new(start_pos, end_pos, speed, start_time))
+        } else if height_diff <= MAX_HOP_HEIGHT {
+            StepMovement::Hop(HopMovement::new(start_pos, end_pos, speed, start_time))
+        } else {
+            StepMovement::Climb(ClimbMovement::new(start_pos, end_pos, speed, start_time))
+        }
+    }
+}
+
+impl Transformer for StepMovement {
+    fn update(&self, transform: &mut Transform, time: f64) {
+        match self {
+            StepMovement::Walk(movement) => movement.update(transform, time),
+            StepMovement::Hop(movement) => movement.update(transform, time),
+            StepMovement::Climb(movement) => movement.update(transform, time),
+        }
+    }
+
+    fn start_time(&self) -> f64 {
+        match self {
+            StepMovement::Walk(movement) => movement.start_time(),
+            StepMovement::Hop(movement) => movement.start_time(),
+            StepMovement::Climb(movement) => movement.start_time(),
+        }
+    }
+
+    fn end_time(&self) -> f64 {
+        match self {
+            StepMovement::Walk(movement) => movement.end_time(),
+            StepMovement::Hop(movement) => movement.end_time(),
+            StepMovement::Climb(movement) => movement.end_time(),
         }
     }
 }
 
 /// Moves piece from its starting coord to another coord,
 /// moving to intermediate tiles along a straight line bewteen the two
+/// or along any other path of neighboring tiles
 pub struct HexPathingLine {
     transformers: TransformerSeries
 }
 
 impl HexPathingLine {
-    pub fn new(start: HexCoord, end: HexCoord, speed: f32, map: &HeightMap) -> HexPathingLine {
-        let move_duration = (HEX_SMALL_DIAMETER / speed) as f64;
-        let line = start.line_between(end);
+    pub fn new(
+        start: HexCoord,
+        end: HexCoord,
+        speed: f32,
+        start_time: f64,
+        map: &HeightMap,
+        layout: &HexLayout
+    ) -> HexPathingLine {
+        Self::along_path(&start.line_between(end), speed, start_time, map, layout)
+    }
+
+    /// Moves through each tile of path in order. Each tile must neighbor the one before it
+    pub fn along_path(
+        path: &[HexCoord],
+        speed: f32,
+        start_time: f64,
+        map: &HeightMap,
+        layout: &HexLayout
+    ) -> HexPathingLine {
+        match path.first() {
+            Some(first) => {
+                let position = map.world_position(*first, layout);
+                Self::from_position(position, path, speed, start_time, map, layout)
+            },
+            None => Self { transformers: TransformerSeries::new() }
+        }
+    }
+
+    /// Same as `along_path` but first moves from position onto the first tile.
+    /// Use the piece's current translation to redirect it mid move without snapping
+    pub fn from_position(
+        position: Vec3,
+        path: &[HexCoord],
+        speed: f32,
+        start_time: f64,
+        map: &HeightMap,
+        layout: &HexLayout
+    ) -> HexPathingLine {
         let mut transformers = TransformerSeries::new();
-    
-        for (i, this_coord) in line.iter().enumerate() {
-            let this_pos = this_coord.to_world(Some(map));
-    
-            if let Some(next_coord) = line.get(i + 1) {
-                let next_pos = next_coord.to_world(Some(map));
-
-                // TODO:
-                // Handle height differences here so we don't clip
-                // Either do a horizontal and vertical movement seperately
-                // or add some kind of bezier curve jump to get over height difference
-
-                let transformer = LinearMovement::new(this_pos, next_pos, speed, now() + move_duration * i as f64);
-                transformers.push(transformer)
+        let mut this_pos = position;
+
+        for coord in path {
+            let next_pos = map.world_position(*coord, layout);
+            if this_pos.distance(next_pos) > f32::EPSILON {
+                transformers.then(StepMovement::new(this_pos, next_pos, speed, start_time))
             }
+            this_pos = next_pos;
         }
         Self { transformers }
     }
@@ -204,7 +663,202 @@ impl Transformer for HexPathingLine {
         self.transformers.update(transform, time)
     }
 
-    fn is_finished(&self, time: f64) -> bool {
-        self.transformers.is_finished(time)
+    fn start_time(&self) -> f64 {
+        self.transformers.start_time()
+    }
+
+    fn end_time(&self) -> f64 {
+        self.transformers.end_time()
+    }
+}
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Tests ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::ManualEventReader;
+
+    use super::*;
+
+    /// Headless app with the clock paused so time only moves through `GameClock::advance`
+    fn test_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugin(TransformationPlugin);
+        app.world.resource_mut::<GameClock>().pause();
+        app
+    }
+
+    fn advance(app: &mut App, ms: f64) {
+        app.world.resource_mut::<GameClock>().advance(ms);
+        app.update();
+    }
+
+    /// Events of type E sent since reader last read
+    fn count<E: bevy::ecs::event::Event>(app: &App, reader: &mut ManualEventReader<E>) -> usize {
+        reader.iter(app.world.resource::<Events<E>>()).count()
+    }
+
+    #[test]
+    fn paused_clock_steps_movement_exactly() {
+        let mut app = test_app();
+        let mut finished = ManualEventReader::<TransformationFinished>::default();
+        // 8 units at 0.5 units per ms, so it ends at 16ms
+        let movement = LinearMovement::new(Vec3::ZERO, Vec3::new(8., 0., 0.), 0.5, 0.);
+        let entity = app.world.spawn((Transform::default(), Transformation::new(movement))).id();
+
+        app.update();
+        assert_eq!(app.world.get::<Transform>(entity).unwrap().translation, Vec3::ZERO);
+
+        // real frames pass but the paused clock holds still
+        app.update();
+        assert_eq!(app.world.resource::<GameClock>().now(), 0.);
+
+        advance(&mut app, 4.);
+        assert_eq!(app.world.get::<Transform>(entity).unwrap().translation, Vec3::new(2., 0., 0.));
+        assert_eq!(count(&app, &mut finished), 0);
+
+        advance(&mut app, 16.);
+        assert_eq!(app.world.get::<Transform>(entity).unwrap().translation, Vec3::new(8., 0., 0.));
+        assert!(app.world.get::<Transformation>(entity).is_none());
+        assert_eq!(count(&app, &mut finished), 1);
+
+        // events only live two frames so read after each
+        advance(&mut app, 4.);
+        assert_eq!(count(&app, &mut finished), 0);
+        advance(&mut app, 4.);
+        assert_eq!(count(&app, &mut finished), 0);
+        assert_eq!(app.world.get::<Transform>(entity).unwrap().translation, Vec3::new(8., 0., 0.));
+    }
+
+    /// Entity to give a new move to on the next update
+    #[derive(Resource, Default)]
+    struct NewOrder(Option<Entity>);
+
+    fn issue_order(mut commands: Commands, mut order: ResMut<NewOrder>, clock: Res<GameClock>) {
+        if let Some(entity) = order.0.take() {
+            let movement = LinearMovement::new(Vec3::X, Vec3::new(5., 0., 0.), 0.5, clock.now());
+            commands.entity(entity).start_transformation(movement);
+        }
+    }
+
+    /// A move ordered on the frame the last one ends replaces it, whichever system's commands apply first
+    fn order_as_move_ends(issue_first: bool) {
+        let mut app = test_app();
+        app.init_resource::<NewOrder>();
+        if issue_first {
+            app.add_system(issue_order.before(transformation_driver));
+        } else {
+            app.add_system(issue_order.after(transformation_driver));
+        }
+        let mut finished = ManualEventReader::<TransformationFinished>::default();
+        let mut cancelled = ManualEventReader::<TransformationCancelled>::default();
+        let movement = LinearMovement::new(Vec3::ZERO, Vec3::X, 0.5, 0.);
+        let entity = app.world.spawn((Transform::default(), Transformation::new(movement))).id();
+        app.update();
+
+        app.world.resource_mut::<NewOrder>().0 = Some(entity);
+        advance(&mut app, 2.);
+        assert!(app.world.get::<Transformation>(entity).is_some());
+        assert_eq!(count(&app, &mut finished), 1);
+        assert_eq!(count(&app, &mut cancelled), 0);
+
+        advance(&mut app, 8.);
+        assert_eq!(app.world.get::<Transform>(entity).unwrap().translation, Vec3::new(5., 0., 0.));
+        assert_eq!(count(&app, &mut finished), 1);
+    }
+
+    #[test]
+    fn order_issued_before_driver_as_move_ends() {
+        order_as_move_ends(true);
+    }
+
+    #[test]
+    fn order_issued_after_driver_as_move_ends() {
+        order_as_move_ends(false);
+    }
+
+    fn at(transformer: &impl Transformer, time: f64) -> Vec3 {
+        let mut transform = Transform::default();
+        transformer.update(&mut transform, time);
+        transform.translation
+    }
+
+    /// Neighboring tile centers, level and with hoppable and climbable drops both ways
+    const STEPS: [(Vec3, Vec3); 6] = [
+        (Vec3::new(0., 1., 0.), Vec3::new(1.73, 1., 0.)),
+        (Vec3::new(0.3, 0.45, -1.1), Vec3::new(1.166, 1.2, 0.4)),
+        (Vec3::new(0.3, 1.2, -1.1), Vec3::new(1.166, 0.45, 0.4)),
+        (Vec3::new(-2., 0.1, 5.), Vec3::new(-2.866, 1.1, 6.5)),
+        (Vec3::new(4., 0.7, 4.), Vec3::new(5.73, 3.9, 4.)),
+        (Vec3::new(4., 3.9, 4.), Vec3::new(5.73, 0.7, 4.)),
+    ];
+
+    #[test]
+    fn hop_lands_exactly() {
+        for (start, end) in STEPS {
+            let hop = HopMovement::new(start, end, 0.7, 3.);
+            assert_eq!(at(&hop, hop.start_time()), start);
+            assert_eq!(at(&hop, hop.end_time()), end);
+            assert_eq!(at(&hop, hop.end_time() + 10.), end);
+        }
+    }
+
+    #[test]
+    fn hop_peaks_above_the_higher_tile() {
+        for (start, end) in STEPS {
+            let hop = HopMovement::new(start, end, 0.7, 3.);
+            let peak = (0..=1000)
+                .map(|i| at(&hop, hop.start_time() + hop.duration() * i as f64 / 1000.).y)
+                .fold(f32::NEG_INFINITY, f32::max);
+            let expected = f32::max(start.y, end.y) + HOP_HEIGHT;
+            assert!((peak - expected).abs() < 1e-3, "peaked at {} not {} from {:?} to {:?}", peak, expected, start, end);
+        }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn climb_goes_straight_up_the_wall() {
+        for (start, end) in STEPS {
+            let climb = ClimbMovement::new(start, end, 0.7, 3.);
+            assert_eq!(at(&climb, climb.start_time()), start);
+            assert!(at(&climb, climb.end_time()).abs_diff_eq(end, 1e-5));
+            let (low, high) = (f32::min(start.y, end.y), f32::max(start.y, end.y));
+            // anywhere between the two heights is on the wall, which is straight up and down
+            let mut wall: Option<Vec2> = None;
+            for i in 0..=200 {
+                let position = at(&climb, climb.start_time() + climb.duration() * i as f64 / 200.);
+                assert!(position.y >= low - 1e-5 && position.y <= high + 1e-5);
+                if position.y > low + 1e-5 && position.y < high - 1e-5 {
+                    let across = Vec2::new(position.x, position.z);
+                    let wall = *wall.get_or_insert(across);
+                    assert!(across.abs_diff_eq(wall, 1e-5), "left the wall at step {}: {:?}", i, position);
+                }
+            }
+            if high > low {
+                assert!(wall.is_some(), "never climbed from {:?} to {:?}", start, end);
+            }
+        }
+    }
+
+    #[test]
+    fn climb_is_slower_than_walking() {
+        let (start, end) = (Vec3::ZERO, Vec3::new(1., 2., 0.));
+        let climb = ClimbMovement::new(start, end, 1., 0.);
+        let walk = 1.;
+        let wall = 2. / CLIMB_SPEED_FACTOR;
+        assert!((climb.duration() - (walk + wall) as f64).abs() < 1e-5);
+    }
+
+    #[test]
+    fn step_picks_hop_or_climb_at_the_threshold() {
+        let start = Vec3::new(0., 2., 0.);
+        let step = |rise: f32| StepMovement::new(start, Vec3::new(1., 2. + rise, 0.), 1., 0.);
+        for rise in [0., -0.] {
+            assert!(matches!(step(rise), StepMovement::Walk(_)));
+        }
+        for rise in [0.01, MAX_HOP_HEIGHT, -0.01, -MAX_HOP_HEIGHT] {
+            assert!(matches!(step(rise), StepMovement::Hop(_)), "{} should hop", rise);
+        }
+        for rise in [MAX_HOP_HEIGHT + 0.01, -MAX_HOP_HEIGHT - 0.01, 1.5] {
+            assert!(matches!(step(rise), StepMovement::Climb(_)), "{} should climb", rise);
+        }
+    }
+}
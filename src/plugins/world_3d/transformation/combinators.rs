@@ -0,0 +1,350 @@
+//! Transformers built out of other transformers.
+//!
+//! Each one moves its inner transformers around in time relative to where they
+//! already start, so they can be nested freely and always know their own length
+use bevy::prelude::*;
+
+use super::Transformer;
+
+/// Plays every transformer at once. Finishes when the last one does
+pub struct Parallel {
+    transformers: Vec<Box<dyn Transformer>>
+}
+
+impl Parallel {
+    pub fn new() -> Self {
+        Self { transformers: Vec::new() }
+    }
+
+    pub fn with(mut self, transformer: impl Transformer) -> Self {
+        self.push(transformer);
+        self
+    }
+
+    pub fn push(&mut self, transformer: impl Transformer) {
+        self.transformers.push(Box::new(transformer))
+    }
+}
+
+impl Default for Parallel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transformer for Parallel {
+    fn update(&self, transform: &mut Transform, time: f64) {
+        for transformer in &self.transformers {
+            // ones that haven't started yet shouldn't snap to their start position
+            if time >= transformer.start_time() {
+                transformer.update(transform, time);
+            }
+        }
+    }
+
+    fn start_time(&self) -> f64 {
+        self.transformers.iter()
+            .map(|transformer| transformer.start_time())
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    fn end_time(&self) -> f64 {
+        self.transformers.iter()
+            .map(|transformer| transformer.end_time())
+            .fold(f64::NEG_INFINITY, f64::max)
+    }
+}
+
+/// Shifts a transformer later in time
+pub struct Delay<T: Transformer> {
+    transformer: T,
+    delay: f64
+}
+
+impl<T: Transformer> Delay<T> {
+    /// Starts delay ms after transformer would have
+    pub fn new(transformer: T, delay: f64) -> Self {
+        Self { transformer, delay }
+    }
+
+    /// Starts at start_time no matter when transformer would have.
+    /// Transformers with no start, like an empty series, are left as they are and count as already finished
+    pub fn starting_at(transformer: T, start_time: f64) -> Self {
+        let delay = match transformer.start_time() {
+            start if start.is_finite() => start_time - start,
+            _ => 0.
+        };
+        Self { transformer, delay }
+    }
+}
+
+impl<T: Transformer> Transformer for Delay<T> {
+    fn update(&self, transform: &mut Transform, time: f64) {
+        self.transformer.update(transform, time - self.delay)
+    }
+
+    fn start_time(&self) -> f64 {
+        self.transformer.start_time() + self.delay
+    }
+
+    fn end_time(&self) -> f64 {
+        self.transformer.end_time() + self.delay
+    }
+}
+
+/// Plays a transformer again from the start every time it finishes
+pub struct Repeat<T: Transformer> {
+    transformer: T,
+    /// None to loop forever
+    times: Option<u32>
+}
+
+impl<T: Transformer> Repeat<T> {
+    pub fn new(transformer: T, times: u32) -> Self {
+        Self { transformer, times: Some(times) }
+    }
+
+    /// Never finishes
+    pub fn forever(transformer: T) -> Self {
+        Self { transformer, times: None }
+    }
+}
+
+impl<T: Transformer> Transformer for Repeat<T> {
+    fn update(&self, transform: &mut Transform, time: f64) {
+        let start = self.transformer.start_time();
+        let duration = self.transformer.duration();
+        if duration <= 0. || time >= self.end_time() {
+            self.transformer.update(transform, f64::min(time, self.transformer.end_time()));
+            return;
+        }
+        let elapsed = f64::max(time - start, 0.);
+        self.transformer.update(transform, start + elapsed % duration);
+    }
+
+    fn start_time(&self) -> f64 {
+        self.transformer.start_time()
+    }
+
+    fn end_time(&self) -> f64 {
+        match self.times {
+            Some(times) => self.transformer.start_time() + self.transformer.duration() * times as f64,
+            None => f64::INFINITY
+        }
+    }
+}
+
+/// Plays a transformer forward then backward, ending back where it started
+pub struct PingPong<T: Transformer> {
+    transformer: T,
+    /// Round trips to make. None to loop forever
+    times: Option<u32>
+}
+
+impl<T: Transformer> PingPong<T> {
+    pub fn new(transformer: T, times: u32) -> Self {
+        Self { transformer, times: Some(times) }
+    }
+
+    /// Never finishes
+    pub fn forever(transformer: T) -> Self {
+        Self { transformer, times: None }
+    }
+}
+
+impl<T: Transformer> Transformer for PingPong<T> {
+    fn update(&self, transform: &mut Transform, time: f64) {
+        let start = self.transformer.start_time();
+        let duration = self.transformer.duration();
+        if duration <= 0. || time >= self.end_time() {
+            self.transformer.update(transform, start);
+            return;
+        }
+        let elapsed = f64::max(time - start, 0.) % (2. * duration);
+        let local = if elapsed <= duration {
+            elapsed
+        } else {
+            2. * duration - elapsed
+        };
+        self.transformer.update(transform, start + local);
+    }
+
+    fn start_time(&self) -> f64 {
+        self.transformer.start_time()
+    }
+
+    fn end_time(&self) -> f64 {
+        match self.times {
+            Some(times) => self.transformer.start_time() + 2. * self.transformer.duration() * times as f64,
+            None => f64::INFINITY
+        }
+    }
+}
+
+/// Plays a transformer backward over the same stretch of time
+pub struct Reverse<T: Transformer> {
+    transformer: T
+}
+
+impl<T: Transformer> Reverse<T> {
+    pub fn new(transformer: T) -> Self {
+        Self { transformer }
+    }
+}
+
+impl<T: Transformer> Transformer for Reverse<T> {
+    fn update(&self, transform: &mut Transform, time: f64) {
+        let start = self.transformer.start_time();
+        let end = self.transformer.end_time();
+        let time = f64::max(start, f64::min(time, end));
+        self.transformer.update(transform, start + end - time)
+    }
+
+    fn start_time(&self) -> f64 {
+        self.transformer.start_time()
+    }
+
+    fn end_time(&self) -> f64 {
+        self.transformer.end_time()
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Tests ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::world_3d::transformation::TransformerSeries;
+
+    /// Sets one axis of the translation to the time, clamped to start..end.
+    /// Leaves the other axes alone so parallel ramps can be told apart
+    struct Ramp {
+        axis: usize,
+        start: f64,
+        end: f64
+    }
+
+    fn ramp(start: f64, end: f64) -> Ramp {
+        Ramp { axis: 0, start, end }
+    }
+
+    impl Transformer for Ramp {
+        fn update(&self, transform: &mut Transform, time: f64) {
+            transform.translation[self.axis] = time.clamp(self.start, self.end) as f32;
+        }
+
+        fn start_time(&self) -> f64 {
+            self.start
+        }
+
+        fn end_time(&self) -> f64 {
+            self.end
+        }
+    }
+
+    fn at(transformer: &impl Transformer, time: f64) -> Vec3 {
+        let mut transform = Transform::default();
+        transformer.update(&mut transform, time);
+        transform.translation
+    }
+
+    #[test]
+    fn parallel_spans_every_transformer() {
+        let parallel = Parallel::new()
+            .with(ramp(0., 10.))
+            .with(Ramp { axis: 1, start: 5., end: 20. });
+        assert_eq!((parallel.start_time(), parallel.end_time()), (0., 20.));
+        // the second hasn't started so it leaves its axis alone
+        assert_eq!(at(&parallel, 3.), Vec3::new(3., 0., 0.));
+        assert_eq!(at(&parallel, 5.), Vec3::new(5., 5., 0.));
+        assert_eq!(at(&parallel, 10.), Vec3::new(10., 10., 0.));
+        assert_eq!(at(&parallel, 20.), Vec3::new(10., 20., 0.));
+        assert!(!parallel.is_finished(19.9));
+        assert!(parallel.is_finished(20.));
+    }
+
+    #[test]
+    fn delay_shifts_both_ends() {
+        let delay = Delay::new(ramp(0., 10.), 5.);
+        assert_eq!((delay.start_time(), delay.end_time()), (5., 15.));
+        assert_eq!(at(&delay, 5.).x, 0.);
+        assert_eq!(at(&delay, 10.).x, 5.);
+        assert_eq!(at(&delay, 15.).x, 10.);
+
+        let delay = Delay::starting_at(ramp(2., 4.), 10.);
+        assert_eq!((delay.start_time(), delay.end_time()), (10., 12.));
+        assert_eq!(at(&delay, 12.).x, 4.);
+
+        // nothing to play, so it's done whenever it's asked
+        let empty = Delay::starting_at(TransformerSeries::new(), 10.);
+        assert!(empty.is_finished(0.));
+    }
+
+    #[test]
+    fn repeat_restarts_at_each_period() {
+        let repeat = Repeat::new(ramp(2., 6.), 3);
+        assert_eq!((repeat.start_time(), repeat.end_time()), (2., 14.));
+        assert_eq!(at(&repeat, 0.).x, 2.);
+        assert_eq!(at(&repeat, 2.).x, 2.);
+        assert_eq!(at(&repeat, 4.).x, 4.);
+        // exactly one period in, the next loop has just begun
+        assert_eq!(at(&repeat, 6.).x, 2.);
+        assert_eq!(at(&repeat, 7.).x, 3.);
+        assert_eq!(at(&repeat, 10.).x, 2.);
+        // the last loop finishes at its end rather than wrapping back
+        assert_eq!(at(&repeat, 14.).x, 6.);
+        assert_eq!(at(&repeat, 20.).x, 6.);
+        assert!(repeat.is_finished(14.));
+    }
+
+    #[test]
+    fn repeat_forever() {
+        let repeat = Repeat::forever(ramp(2., 6.));
+        assert_eq!(repeat.end_time(), f64::INFINITY);
+        assert_eq!(at(&repeat, 102.).x, 2.);
+        assert_eq!(at(&repeat, 103.).x, 3.);
+        assert!(!repeat.is_finished(1e9));
+    }
+
+    #[test]
+    fn repeat_of_nothing_ends_at_once() {
+        let repeat = Repeat::new(ramp(3., 3.), 5);
+        assert_eq!(repeat.end_time(), 3.);
+        assert_eq!(at(&repeat, 5.).x, 3.);
+    }
+
+    #[test]
+    fn ping_pong_turns_around_at_the_end() {
+        let ping_pong = PingPong::new(ramp(2., 6.), 2);
+        assert_eq!((ping_pong.start_time(), ping_pong.end_time()), (2., 18.));
+        assert_eq!(at(&ping_pong, 2.).x, 2.);
+        assert_eq!(at(&ping_pong, 6.).x, 6.);
+        assert_eq!(at(&ping_pong, 8.).x, 4.);
+        // exactly one round trip in
+        assert_eq!(at(&ping_pong, 10.).x, 2.);
+        assert_eq!(at(&ping_pong, 14.).x, 6.);
+        assert_eq!(at(&ping_pong, 17.).x, 3.);
+        // ends back where it started
+        assert_eq!(at(&ping_pong, 18.).x, 2.);
+        assert_eq!(at(&ping_pong, 30.).x, 2.);
+    }
+
+    #[test]
+    fn ping_pong_forever() {
+        let ping_pong = PingPong::forever(ramp(2., 6.));
+        assert_eq!(ping_pong.end_time(), f64::INFINITY);
+        assert_eq!(at(&ping_pong, 82.).x, 2.);
+        assert_eq!(at(&ping_pong, 86.).x, 6.);
+    }
+
+    #[test]
+    fn reverse_plays_backward() {
+        let reverse = Reverse::new(ramp(2., 6.));
+        assert_eq!((reverse.start_time(), reverse.end_time()), (2., 6.));
+        assert_eq!(at(&reverse, 0.).x, 6.);
+        assert_eq!(at(&reverse, 2.).x, 6.);
+        assert_eq!(at(&reverse, 3.).x, 5.);
+        assert_eq!(at(&reverse, 6.).x, 2.);
+        assert_eq!(at(&reverse, 9.).x, 2.);
+    }
+}
@@ -0,0 +1,331 @@
+//! Curves for speeding up and slowing down transformers.
+//! See: https://easings.net
+use std::f64::consts::PI;
+
+use bevy::prelude::*;
+
+use super::Transformer;
+
+/// Maps progress through an animation, 0 at the start and 1 at the end,
+/// to how far along the animation should be drawn.
+///
+/// Back and Elastic curves overshoot and go a little below 0 or above 1
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Easing {
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    BackIn,
+    BackOut,
+    BackInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+    /// Same as css `cubic-bezier(x1, y1, x2, y2)`. The curve runs from (0,0) to (1,1)
+    /// and x1, x2 must be in 0..=1 so there is one y for every x.
+    /// See: https://developer.mozilla.org/en-US/docs/Web/CSS/easing-function#cubic_b%C3%A9zier_easing_function
+    CubicBezier(f64, f64, f64, f64)
+}
+
+const BACK_OVERSHOOT: f64 = 1.70158;
+const BACK_IN_OUT_OVERSHOOT: f64 = BACK_OVERSHOOT * 1.525;
+const ELASTIC_PERIOD: f64 = 2. * PI / 3.;
+const ELASTIC_IN_OUT_PERIOD: f64 = 2. * PI / 4.5;
+
+impl Easing {
+    /// x: progress through the animation in 0..=1
+    pub fn ease(&self, x: f64) -> f64 {
+        let x = x.clamp(0., 1.);
+        match *self {
+            Easing::Linear => x,
+            Easing::QuadIn => x * x,
+            Easing::QuadOut => 1. - (1. - x).powi(2),
+            Easing::QuadInOut => {
+                if x < 0.5 { 2. * x * x } else { 1. - (-2. * x + 2.).powi(2) / 2. }
+            },
+            Easing::CubicIn => x * x * x,
+            Easing::CubicOut => 1. - (1. - x).powi(3),
+            Easing::CubicInOut => {
+                if x < 0.5 { 4. * x * x * x } else { 1. - (-2. * x + 2.).powi(3) / 2. }
+            },
+            Easing::SineIn => 1. - (x * PI / 2.).cos(),
+            Easing::SineOut => (x * PI / 2.).sin(),
+            Easing::SineInOut => -((PI * x).cos() - 1.) / 2.,
+            Easing::BackIn => {
+                (BACK_OVERSHOOT + 1.) * x * x * x - BACK_OVERSHOOT * x * x
+            },
+            Easing::BackOut => {
+                1. + (BACK_OVERSHOOT + 1.) * (x - 1.).powi(3) + BACK_OVERSHOOT * (x - 1.).powi(2)
+            },
+            Easing::BackInOut => {
+                let c = BACK_IN_OUT_OVERSHOOT;
+                if x < 0.5 {
+                    (2. * x).powi(2) * ((c + 1.) * 2. * x - c) / 2.
+                } else {
+                    ((2. * x - 2.).powi(2) * ((c + 1.) * (2. * x - 2.) + c) + 2.) / 2.
+                }
+            },
+            Easing::ElasticIn => {
+                if x == 0. || x == 1. {
+                    return x;
+                }
+                -(2f64.powf(10. * x - 10.)) * ((10. * x - 10.75) * ELASTIC_PERIOD).sin()
+            },
+            Easing::ElasticOut => {
+                if x == 0. || x == 1. {
+                    return x;
+                }
+                2f64.powf(-10. * x) * ((10. * x - 0.75) * ELASTIC_PERIOD).sin() + 1.
+            },
+            Easing::ElasticInOut => {
+                if x == 0. || x == 1. {
+                    return x;
+                }
+                let wave = ((20. * x - 11.125) * ELASTIC_IN_OUT_PERIOD).sin();
+                if x < 0.5 {
+                    -(2f64.powf(20. * x - 10.) * wave) / 2.
+                } else {
+                    2f64.powf(-20. * x + 10.) * wave / 2. + 1.
+                }
+            },
+            Easing::BounceIn => 1. - bounce_out(1. - x),
+            Easing::BounceOut => bounce_out(x),
+            Easing::BounceInOut => {
+                if x < 0.5 {
+                    (1. - bounce_out(1. - 2. * x)) / 2.
+                } else {
+                    (1. + bounce_out(2. * x - 1.)) / 2.
+                }
+            },
+            Easing::CubicBezier(x1, y1, x2, y2) => {
+                let t = solve_bezier_t(x, x1, x2);
+                bezier(t, y1, y2)
+            },
+        }
+    }
+}
+
+fn bounce_out(x: f64) -> f64 {
+    const N: f64 = 7.5625;
+    const D: f64 = 2.75;
+    if x < 1. / D {
+        N * x * x
+    } else if x < 2. / D {
+        let x = x - 1.5 / D;
+        N * x * x + 0.75
+    } else if x < 2.5 / D {
+        let x = x - 2.25 / D;
+        N * x * x + 0.9375
+    } else {
+        let x = x - 2.625 / D;
+        N * x * x + 0.984375
+    }
+}
+
+/// One axis of a cubic bezier from 0 to 1 with control points p1 and p2
+fn bezier(t: f64, p1: f64, p2: f64) -> f64 {
+    let u = 1. - t;
+    3. * u * u * t * p1 + 3. * u * t * t * p2 + t * t * t
+}
+
+fn bezier_slope(t: f64, p1: f64, p2: f64) -> f64 {
+    let u = 1. - t;
+    3. * u * u * p1 + 6. * u * t * (p2 - p1) + 3. * t * t * (1. - p2)
+}
+
+/// The t where the x axis of the curve reaches x.
+/// Newton's method is quick when the slope is steep, bisection catches the flat spots
+fn solve_bezier_t(x: f64, x1: f64, x2: f64) -> f64 {
+    const EPSILON: f64 = 1e-7;
+    let mut t = x;
+    for _ in 0..8 {
+        let error = bezier(t, x1, x2) - x;
+        if error.abs() < EPSILON {
+            return t;
+        }
+        let slope = bezier_slope(t, x1, x2);
+        if slope.abs() < EPSILON {
+            break;
+        }
+        t -= error / slope;
+    }
+
+    let (mut low, mut high) = (0., 1.);
+    t = x;
+    while high - low > EPSILON {
+        if bezier(t, x1, x2) < x {
+            low = t;
+        } else {
+            high = t;
+        }
+        t = (low + high) / 2.;
+    }
+    t
+}
+
+/// Plays a transformer along an easing curve.
+///
+/// The wrapped transformer keeps its start and end time,
+/// only how fast it moves in between changes
+pub struct Eased<T: Transformer> {
+    transformer: T,
+    easing: Easing
+}
+
+impl<T: Transformer> Eased<T> {
+    pub fn new(transformer: T, easing: Easing) -> Self {
+        Self { transformer, easing }
+    }
+}
+
+impl<T: Transformer> Transformer for Eased<T> {
+    fn update(&self, transform: &mut Transform, time: f64) {
+        let start = self.transformer.start_time();
+        let duration = self.transformer.duration();
+        if duration <= 0. {
+            self.transformer.update(transform, time);
+            return;
+        }
+        let progress = self.easing.ease((time - start) / duration);
+        if (0. ..=1.).contains(&progress) {
+            self.transformer.update(transform, start + progress * duration);
+            return;
+        }
+
+        // transformers stop at their ends so overshoot by mirroring
+        // the state the same distance back inside the animation
+        let (edge_progress, inside_progress) = if progress > 1. {
+            (1., 2. - progress)
+        } else {
+            (0., -progress)
+        };
+        let mut edge = *transform;
+        self.transformer.update(&mut edge, start + edge_progress * duration);
+        let mut inside = *transform;
+        self.transformer.update(&mut inside, start + inside_progress * duration);
+
+        transform.translation = 2. * edge.translation - inside.translation;
+        transform.scale = 2. * edge.scale - inside.scale;
+        transform.rotation = (edge.rotation * inside.rotation.inverse() * edge.rotation).normalize();
+    }
+
+    fn start_time(&self) -> f64 {
+        self.transformer.start_time()
+    }
+
+    fn end_time(&self) -> f64 {
+        self.transformer.end_time()
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Tests ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::world_3d::transformation::LinearMovement;
+
+    /// css `ease`, `ease-in-out` and `ease-in`
+    const CSS_EASE: Easing = Easing::CubicBezier(0.25, 0.1, 0.25, 1.);
+    const CSS_EASE_IN_OUT: Easing = Easing::CubicBezier(0.42, 0., 0.58, 1.);
+    const CSS_EASE_IN: Easing = Easing::CubicBezier(0.42, 0., 1., 1.);
+
+    const ALL: [Easing; 23] = [
+        Easing::Linear,
+        Easing::QuadIn,
+        Easing::QuadOut,
+        Easing::QuadInOut,
+        Easing::CubicIn,
+        Easing::CubicOut,
+        Easing::CubicInOut,
+        Easing::SineIn,
+        Easing::SineOut,
+        Easing::SineInOut,
+        Easing::BackIn,
+        Easing::BackOut,
+        Easing::BackInOut,
+        Easing::ElasticIn,
+        Easing::ElasticOut,
+        Easing::ElasticInOut,
+        Easing::BounceIn,
+        Easing::BounceOut,
+        Easing::BounceInOut,
+        CSS_EASE,
+        CSS_EASE_IN_OUT,
+        CSS_EASE_IN,
+        // flat at both ends, so Newton's method gives up there
+        Easing::CubicBezier(1., 0., 0., 1.),
+    ];
+
+    #[test]
+    fn starts_at_0_and_ends_at_1() {
+        for easing in ALL {
+            assert!(easing.ease(0.).abs() < 1e-6, "{:?} starts at {}", easing, easing.ease(0.));
+            assert!((easing.ease(1.) - 1.).abs() < 1e-6, "{:?} ends at {}", easing, easing.ease(1.));
+            // progress outside the animation is clamped
+            assert_eq!(easing.ease(-1.), easing.ease(0.));
+            assert_eq!(easing.ease(2.), easing.ease(1.));
+        }
+    }
+
+    #[test]
+    fn cubic_bezier_matches_css() {
+        // worked out by bisecting the same curves to full precision
+        let known = [
+            (CSS_EASE, [0.094796, 0.408511, 0.802403, 0.960459, 0.994316]),
+            (CSS_EASE_IN_OUT, [0.019722, 0.129162, 0.5, 0.870838, 0.980278]),
+            (CSS_EASE_IN, [0.017027, 0.093465, 0.315357, 0.621862, 0.839428]),
+        ];
+        for (easing, values) in known {
+            for (x, expected) in [0.1, 0.25, 0.5, 0.75, 0.9].into_iter().zip(values) {
+                let eased = easing.ease(x);
+                assert!((eased - expected).abs() < 1e-5, "{:?} at {} gave {} not {}", easing, x, eased, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn cubic_bezier_flat_spots() {
+        let steep = Easing::CubicBezier(1., 0., 0., 1.);
+        assert!((steep.ease(0.5) - 0.5).abs() < 1e-6);
+        let mut previous = 0.;
+        for i in 0..=100 {
+            let eased = steep.ease(i as f64 / 100.);
+            assert!(eased >= previous - 1e-9, "went back down at {}", i);
+            previous = eased;
+        }
+    }
+
+    #[test]
+    fn known_values() {
+        assert_eq!(Easing::QuadIn.ease(0.5), 0.25);
+        assert_eq!(Easing::CubicOut.ease(0.5), 0.875);
+        assert!((Easing::SineInOut.ease(0.5) - 0.5).abs() < 1e-12);
+        assert!((Easing::BounceOut.ease(0.5) - 0.765625).abs() < 1e-12);
+        // back curves dip before the start
+        assert!(Easing::BackIn.ease(0.2) < 0.);
+    }
+
+    #[test]
+    fn eased_overshoot_mirrors_past_the_start() {
+        let movement = LinearMovement::new(Vec3::ZERO, Vec3::new(10., 0., 0.), 1., 0.);
+        let eased = movement.eased(Easing::BackIn);
+        let mut transform = Transform::default();
+        eased.update(&mut transform, 2.);
+        let expected = Easing::BackIn.ease(0.2) * 10.;
+        assert!((transform.translation.x as f64 - expected).abs() < 1e-4);
+        assert!(transform.translation.x < 0.);
+        // keeps the wrapped transformer's times
+        assert_eq!((eased.start_time(), eased.end_time()), (0., 10.));
+    }
+}
@@ -0,0 +1,291 @@
+//! Playing transformations one after another on the same entity
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use super::{GameClock, Transformation, TransformationCommandsExt};
+
+type Builder = Box<dyn FnOnce(&Transform, f64) -> Transformation + Send + Sync>;
+
+enum Queued {
+    /// Built ahead of time. Shifted to start when its turn comes
+    Ready(Transformation),
+    /// Built when its turn comes from the transform the entity has then
+    Deferred(Builder)
+}
+
+impl Queued {
+    fn build(self, transform: &Transform, start_time: f64) -> Transformation {
+        match self {
+            Queued::Ready(transformation) => transformation.starting_at(start_time),
+            Queued::Deferred(builder) => builder(transform, start_time),
+        }
+    }
+}
+
+/// Transformations waiting to run on this entity.
+///
+/// Each one starts once the entity has no `Transformation` running,
+/// so a move, then an attack lunge, then a return all play in order
+#[derive(Component, Default)]
+pub struct TransformationQueue {
+    queue: VecDeque<Queued>,
+    /// the front of the queue should cancel whatever is running
+    interrupting: bool
+}
+
+impl TransformationQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Plays transformation after everything already queued
+    pub fn push(&mut self, transformation: impl Into<Transformation>) {
+        self.queue.push_back(Queued::Ready(transformation.into()))
+    }
+
+    /// Like `push` but builds the transformation when its turn comes,
+    /// from the entity's transform at that point and the time it starts.
+    /// Use when it depends on where earlier transformations leave the entity
+    pub fn push_with<F, T>(&mut self, builder: F)
+    where
+        F: FnOnce(&Transform, f64) -> T + Send + Sync + 'static,
+        T: Into<Transformation>
+    {
+        self.queue.push_back(Self::defer(builder))
+    }
+
+    /// Plays transformation as soon as the running one finishes, ahead of the rest of the queue
+    pub fn push_next(&mut self, transformation: impl Into<Transformation>) {
+        self.queue.push_front(Queued::Ready(transformation.into()))
+    }
+
+    /// Cancels the running transformation and plays this one right away.
+    /// The rest of the queue plays after it
+    pub fn interrupt(&mut self, transformation: impl Into<Transformation>) {
+        self.queue.push_front(Queued::Ready(transformation.into()));
+        self.interrupting = true;
+    }
+
+    /// Like `interrupt` but built from wherever the running transformation left the entity
+    pub fn interrupt_with<F, T>(&mut self, builder: F)
+    where
+        F: FnOnce(&Transform, f64) -> T + Send + Sync + 'static,
+        T: Into<Transformation>
+    {
+        self.queue.push_front(Self::defer(builder));
+        self.interrupting = true;
+    }
+
+    /// Drops everything waiting. Doesn't stop the running transformation
+    pub fn clear(&mut self) {
+        self.queue.clear();
+        self.interrupting = false;
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    fn defer<F, T>(builder: F) -> Queued
+    where
+        F: FnOnce(&Transform, f64) -> T + Send + Sync + 'static,
+        T: Into<Transformation>
+    {
+        Queued::Deferred(Box::new(move |transform, start_time| builder(transform, start_time).into()))
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(super) fn transformation_queue_driver(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    mut query: Query<(Entity, &Transform, &mut TransformationQueue, Option<&Transformation>)>
+) {
+    let now = clock.now();
+    for (entity, transform, mut queue, running) in query.iter_mut() {
+        // check before touching the queue mutably so idle queues aren't marked changed
+        if queue.is_empty() {
+            continue;
+        }
+        let start_time = match running {
+            None => now,
+            Some(_) if queue.interrupting => now,
+            // picks up exactly where the finished one ended so chained moves don't drift by a frame each
+            Some(running) if running.is_finished(now) => match running.end_time() {
+                end if end.is_finite() => end,
+                _ => now
+            },
+            Some(_) => continue
+        };
+        queue.interrupting = false;
+        // build from wherever the running one has the entity right now, not where it was last frame
+        let mut from = *transform;
+        if let Some(running) = running {
+            running.update(&mut from, now);
+        }
+        if let Some(next) = queue.queue.pop_front() {
+            let transformation = next.build(&from, start_time);
+            commands.entity(entity).start_transformation(transformation);
+        }
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Tests ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::{Event, ManualEventReader};
+
+    use super::*;
+    use crate::plugins::world_3d::transformation::{
+        LinearMovement,
+        TransformationCancelled,
+        TransformationFinished,
+        TransformationPlugin,
+        TransformerSeries,
+    };
+
+    /// Headless app with the clock paused so time only moves through `GameClock::advance`
+    fn test_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugin(TransformationPlugin);
+        app.world.resource_mut::<GameClock>().pause();
+        app
+    }
+
+    fn advance(app: &mut App, ms: f64) {
+        app.world.resource_mut::<GameClock>().advance(ms);
+        app.update();
+    }
+
+    fn count<E: Event>(app: &App, reader: &mut ManualEventReader<E>) -> usize {
+        reader.iter(app.world.resource::<Events<E>>()).count()
+    }
+
+    fn translation(app: &App, entity: Entity) -> Vec3 {
+        app.world.get::<Transform>(entity).unwrap().translation
+    }
+
+    /// 1 unit per ms from wherever the entity is when its turn comes
+    fn step_by(offset: Vec3) -> impl FnOnce(&Transform, f64) -> LinearMovement {
+        move |transform, start_time| {
+            LinearMovement::new(transform.translation, transform.translation + offset, 1., start_time)
+        }
+    }
+
+    /// Entity queued to move 4 along x then 4 along y, each taking 4ms
+    fn spawn_queued(app: &mut App) -> Entity {
+        let mut queue = TransformationQueue::new();
+        queue.push(LinearMovement::new(Vec3::ZERO, Vec3::new(4., 0., 0.), 1., 0.));
+        queue.push_with(step_by(Vec3::new(0., 4., 0.)));
+        let entity = app.world.spawn((Transform::default(), queue)).id();
+        // starts the first at 0
+        app.update();
+        entity
+    }
+
+    #[test]
+    fn plays_in_order() {
+        let mut app = test_app();
+        let mut finished = ManualEventReader::<TransformationFinished>::default();
+        let entity = spawn_queued(&mut app);
+
+        advance(&mut app, 2.);
+        assert_eq!(translation(&app, entity), Vec3::new(2., 0., 0.));
+        // the second is built from where the first ends
+        advance(&mut app, 2.);
+        advance(&mut app, 2.);
+        assert_eq!(translation(&app, entity), Vec3::new(4., 2., 0.));
+        assert_eq!(count(&app, &mut finished), 1);
+        advance(&mut app, 2.);
+        advance(&mut app, 0.);
+        assert_eq!(translation(&app, entity), Vec3::new(4., 4., 0.));
+        assert_eq!(count(&app, &mut finished), 1);
+        assert!(app.world.get::<Transformation>(entity).is_none());
+        assert!(app.world.get::<TransformationQueue>(entity).unwrap().is_empty());
+    }
+
+    #[test]
+    fn next_starts_when_the_last_ended() {
+        let mut app = test_app();
+        let entity = spawn_queued(&mut app);
+
+        // the first ends at 4 but isn't seen to until 5. The second still starts at 4
+        advance(&mut app, 5.);
+        advance(&mut app, 1.);
+        assert_eq!(translation(&app, entity), Vec3::new(4., 2., 0.));
+    }
+
+    #[test]
+    fn items_shorter_than_a_frame_still_finish_in_place() {
+        let mut app = test_app();
+        let mut finished = ManualEventReader::<TransformationFinished>::default();
+        let entity = spawn_queued(&mut app);
+
+        // both end within this one frame
+        advance(&mut app, 3.);
+        advance(&mut app, 7.);
+        assert_eq!(translation(&app, entity), Vec3::new(4., 4., 0.));
+        assert_eq!(count(&app, &mut finished), 2);
+        assert!(app.world.get::<Transformation>(entity).is_none());
+    }
+
+    #[test]
+    fn interrupt_cancels_and_keeps_the_rest() {
+        let mut app = test_app();
+        let mut cancelled = ManualEventReader::<TransformationCancelled>::default();
+        let entity = spawn_queued(&mut app);
+
+        advance(&mut app, 1.);
+        app.world.resource_mut::<GameClock>().advance(1.);
+        app.world.get_mut::<TransformationQueue>(entity).unwrap()
+            .interrupt_with(step_by(Vec3::new(0., 0., 2.)));
+        app.update();
+        assert_eq!(count(&app, &mut cancelled), 1);
+        // built from where the first had got to when it was cut off
+        advance(&mut app, 1.);
+        assert_eq!(translation(&app, entity), Vec3::new(2., 0., 1.));
+        // then the rest of the queue carries on from there
+        advance(&mut app, 1.);
+        advance(&mut app, 2.);
+        assert_eq!(translation(&app, entity), Vec3::new(2., 2., 2.));
+    }
+
+    #[test]
+    fn clear_drops_waiting_only() {
+        let mut app = test_app();
+        let entity = spawn_queued(&mut app);
+
+        advance(&mut app, 1.);
+        app.world.get_mut::<TransformationQueue>(entity).unwrap().clear();
+        advance(&mut app, 3.);
+        advance(&mut app, 4.);
+        assert_eq!(translation(&app, entity), Vec3::new(4., 0., 0.));
+    }
+
+    #[test]
+    fn empty_move_does_not_block_the_queue() {
+        let mut app = test_app();
+        let mut finished = ManualEventReader::<TransformationFinished>::default();
+        let mut queue = TransformationQueue::new();
+        // a move to the tile the piece already stands on has no steps
+        queue.push(TransformerSeries::new());
+        queue.push(LinearMovement::new(Vec3::ZERO, Vec3::new(4., 0., 0.), 1., 0.));
+        let entity = app.world.spawn((Transform::default(), queue)).id();
+
+        let mut finishes = 0;
+        for _ in 0..8 {
+            advance(&mut app, 2.);
+            finishes += count(&app, &mut finished);
+        }
+        assert_eq!(finishes, 2);
+        assert_eq!(translation(&app, entity), Vec3::new(4., 0., 0.));
+        assert!(app.world.get::<Transformation>(entity).is_none());
+        assert!(app.world.get::<TransformationQueue>(entity).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,279 @@
+//! Smooth curves through tile centers.
+//! See: https://en.wikipedia.org/wiki/Centripetal_Catmull%E2%80%93Rom_spline
+use bevy::prelude::*;
+
+use crate::hex::{HexCoord, HexLayout};
+use crate::plugins::world_3d::hex::height_map::HeightMap;
+use super::Transformer;
+
+/// Points stored along each segment of the curve to measure distance along it
+const SAMPLES_PER_SEGMENT: usize = 16;
+/// Spacing of the terrain height samples along the curve in world units
+const HEIGHT_SAMPLE_SPACING: f32 = 0.05;
+/// 0.5 makes the spline centripetal, which never loops or overshoots at sharp turns
+const ALPHA: f32 = 0.5;
+
+/// A centripetal Catmull-Rom spline through a list of points,
+/// measured so positions can be looked up by distance along it
+#[derive(Debug, Clone)]
+pub struct SplinePath {
+    /// positions along the curve, SAMPLES_PER_SEGMENT to each segment
+    samples: Vec<Vec3>,
+    /// distance along the curve to each sample
+    distances: Vec<f32>
+}
+
+impl SplinePath {
+    pub fn new(points: &[Vec3]) -> Self {
+        let mut points = points.to_vec();
+        points.dedup_by(|a, b| a.distance(*b) < f32::EPSILON);
+
+        let mut samples = Vec::new();
+        match points.len() {
+            0 => {},
+            1 => samples.push(points[0]),
+            _ => {
+                let last = points.len() - 1;
+                // extend the ends straight out so the curve starts and ends heading along the path
+                let before = 2. * points[0] - points[1];
+                let after = 2. * points[last] - points[last - 1];
+                for i in 0..last {
+                    let p0 = if i == 0 { before } else { points[i - 1] };
+                    let p3 = if i + 1 == last { after } else { points[i + 2] };
+                    for step in 0..SAMPLES_PER_SEGMENT {
+                        let t = step as f32 / SAMPLES_PER_SEGMENT as f32;
+                        samples.push(catmull_rom(p0, points[i], points[i + 1], p3, t));
+                    }
+                }
+                samples.push(points[last]);
+            }
+        }
+
+        let mut distances = Vec::with_capacity(samples.len());
+        let mut total = 0.;
+        for (i, sample) in samples.iter().enumerate() {
+            if i > 0 {
+                total += sample.distance(samples[i - 1]);
+            }
+            distances.push(total);
+        }
+        Self { samples, distances }
+    }
+
+    pub fn length(&self) -> f32 {
+        match self.distances.last() {
+            Some(length) => *length,
+            None => 0.
+        }
+    }
+
+    /// Position distance along the curve. Clamped to the ends
+    pub fn point_at(&self, distance: f32) -> Vec3 {
+        if self.samples.is_empty() {
+            return Vec3::ZERO;
+        }
+        let distance = distance.clamp(0., self.length());
+        // first sample at or past distance
+        let next = self.distances.partition_point(|d| *d < distance);
+        if next == 0 {
+            return self.samples[0];
+        }
+        let (d0, d1) = (self.distances[next - 1], self.distances[next]);
+        let t = if d1 > d0 { (distance - d0) / (d1 - d0) } else { 0. };
+        self.samples[next - 1].lerp(self.samples[next], t)
+    }
+}
+
+/// Position between p1 and p2 at t in 0..1, using the Barry and Goldman pyramid
+fn catmull_rom(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, t: f32) -> Vec3 {
+    // knot spacing grows with the square root of the distance between points
+    let knot = |a: Vec3, b: Vec3| f32::max(a.distance(b).powf(ALPHA), 1e-4);
+    let t0 = 0.;
+    let t1 = t0 + knot(p0, p1);
+    let t2 = t1 + knot(p1, p2);
+    let t3 = t2 + knot(p2, p3);
+    let t = t1 + (t2 - t1) * t;
+
+    let a1 = p0 * ((t1 - t) / (t1 - t0)) + p1 * ((t - t0) / (t1 - t0));
+    let a2 = p1 * ((t2 - t) / (t2 - t1)) + p2 * ((t - t1) / (t2 - t1));
+    let a3 = p2 * ((t3 - t) / (t3 - t2)) + p3 * ((t - t2) / (t3 - t2));
+    let b1 = a1 * ((t2 - t) / (t2 - t0)) + a2 * ((t - t0) / (t2 - t0));
+    let b2 = a2 * ((t3 - t) / (t3 - t1)) + a3 * ((t - t1) / (t3 - t1));
+    b1 * ((t2 - t) / (t2 - t1)) + b2 * ((t - t1) / (t2 - t1))
+}
+
+/// Glides through tile centers along a `SplinePath` at a constant speed.
+///
+/// The curve runs flat across the plane and the height comes from the terrain underneath.
+/// Height changes ramp up before reaching a taller tile and ramp down after leaving one
+/// so pieces don't clip through tile sides
+pub struct SplineMovement {
+    start_time: f64,
+    end_time: f64,
+    speed: f32,
+    path: SplinePath,
+    /// terrain height every HEIGHT_SAMPLE_SPACING along the path, smoothed
+    heights: Vec<f32>
+}
+
+impl SplineMovement {
+    /// Moves through each tile of path in order. Each tile must neighbor the one before it
+    pub fn new(path: &[HexCoord], speed: f32, start_time: f64, map: &HeightMap, layout: &HexLayout) -> Self {
+        let points: Vec<Vec3> = path.iter().map(|coord| layout.to_world(*coord)).collect();
+        Self::through(&points, speed, start_time, map, layout)
+    }
+
+    /// Same as `new` but starts from position. Use the piece's current translation
+    /// to redirect it mid move without snapping
+    pub fn from_position(
+        position: Vec3,
+        path: &[HexCoord],
+        speed: f32,
+        start_time: f64,
+        map: &HeightMap,
+        layout: &HexLayout
+    ) -> Self {
+        let mut position = position;
+        position.y = 0.;
+        let points: Vec<Vec3> = std::iter::once(position)
+            .chain(path.iter().map(|coord| layout.to_world(*coord)))
+            .collect();
+        Self::through(&points, speed, start_time, map, layout)
+    }
+
+    fn through(points: &[Vec3], speed: f32, start_time: f64, map: &HeightMap, layout: &HexLayout) -> Self {
+        let path = SplinePath::new(points);
+        let length = path.length();
+        let sample_count = (length / HEIGHT_SAMPLE_SPACING).ceil() as usize + 1;
+        let raw: Vec<f32> = (0..sample_count)
+            .map(|i| {
+                let point = path.point_at(i as f32 * HEIGHT_SAMPLE_SPACING);
+                map.world_position(layout.from_world(point), layout).y
+            })
+            .collect();
+
+        // take the highest height nearby so climbs finish before the edge and drops start after it,
+        // then average to turn the steps into ramps. Together they reach 80% of the way to a tile's edge
+        let window = (layout.inner_radius() * 0.4 / HEIGHT_SAMPLE_SPACING).round() as usize;
+        let highest = filter(&raw, window, |near| near.iter().cloned().fold(f32::MIN, f32::max));
+        let heights = filter(&highest, window, |near| near.iter().sum::<f32>() / near.len() as f32);
+
+        let end_time = start_time + (length / speed) as f64;
+        Self { start_time, end_time, speed, path, heights }
+    }
+}
+
+/// Replaces each value with f of the values within window of it
+fn filter(values: &[f32], window: usize, f: impl Fn(&[f32]) -> f32) -> Vec<f32> {
+    (0..values.len())
+        .map(|i| {
+            let low = i.saturating_sub(window);
+            let high = usize::min(i + window + 1, values.len());
+            f(&values[low..high])
+        })
+        .collect()
+}
+
+impl Transformer for SplineMovement {
+    fn update(&self, transform: &mut Transform, time: f64) {
+        let time = f64::max(self.start_time, f64::min(time, self.end_time));
+        let distance = (time - self.start_time) as f32 * self.speed;
+        let mut position = self.path.point_at(distance);
+
+        let index = distance / HEIGHT_SAMPLE_SPACING;
+        let below = usize::min(index.floor() as usize, self.heights.len().saturating_sub(1));
+        let above = usize::min(below + 1, self.heights.len().saturating_sub(1));
+        if let (Some(low), Some(high)) = (self.heights.get(below), self.heights.get(above)) {
+            position.y = low + (high - low) * (index - below as f32).clamp(0., 1.);
+        }
+        transform.translation = position;
+    }
+
+    fn start_time(&self) -> f64 {
+        self.start_time
+    }
+
+    fn end_time(&self) -> f64 {
+        self.end_time
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Tests ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::{HexOrientation, WorldPlane};
+    use crate::plugins::world_3d::hex::height_map::FlatGenerator;
+
+    /// Turns back and forth so the curve has to bend
+    const PATH: [HexCoord; 6] = [
+        HexCoord(0, 0),
+        HexCoord(1, 0),
+        HexCoord(1, 1),
+        HexCoord(2, 1),
+        HexCoord(2, 0),
+        HexCoord(3, -1),
+    ];
+
+    fn layout() -> HexLayout {
+        HexLayout::new(HexOrientation::Pointy, 1., WorldPlane::XZ)
+    }
+
+    #[test]
+    fn passes_through_every_point() {
+        let layout = layout();
+        let points: Vec<Vec3> = PATH.iter().map(|coord| layout.to_world(*coord)).collect();
+        let spline = SplinePath::new(&points);
+        assert_eq!(spline.point_at(0.), points[0]);
+        assert_eq!(spline.point_at(spline.length()), points[points.len() - 1]);
+        for point in points.iter() {
+            let closest = spline.samples.iter().map(|sample| sample.distance(*point)).fold(f32::MAX, f32::min);
+            assert!(closest < 1e-4, "{:?} missed by {}", point, closest);
+        }
+        // the curve is longer than the straight line through the points but not by much
+        let polyline: f32 = points.windows(2).map(|pair| pair[0].distance(pair[1])).sum();
+        assert!(spline.length() >= polyline * 0.99 && spline.length() < polyline * 1.2);
+    }
+
+    #[test]
+    fn constant_speed_along_the_curve() {
+        let layout = layout();
+        let map = HeightMap::new(FlatGenerator::new(2));
+        let speed = 0.01;
+        let movement = SplineMovement::new(&PATH, speed, 100., &map, &layout);
+        let steps = 200;
+        let dt = movement.duration() / steps as f64;
+        let positions: Vec<Vec3> = (0..=steps)
+            .map(|i| {
+                let mut transform = Transform::default();
+                movement.update(&mut transform, 100. + dt * i as f64);
+                transform.translation
+            })
+            .collect();
+        let expected = speed * dt as f32;
+        for pair in positions.windows(2) {
+            // chords are a touch shorter than the arc they cut across
+            let step = pair[0].distance(pair[1]);
+            assert!(step > expected * 0.95 && step < expected * 1.01, "moved {} instead of {}", step, expected);
+        }
+    }
+
+    #[test]
+    fn finished_at_end_time() {
+        let layout = layout();
+        let map = HeightMap::new(FlatGenerator::new(2));
+        let movement = SplineMovement::new(&PATH, 0.01, 100., &map, &layout);
+        assert!(!movement.is_finished(movement.end_time() - 1.));
+        assert!(movement.is_finished(movement.end_time()));
+
+        let mut transform = Transform::default();
+        movement.update(&mut transform, movement.end_time());
+        let goal = map.world_position(PATH[PATH.len() - 1], &layout);
+        assert!(transform.translation.abs_diff_eq(goal, 1e-4), "ended at {:?}", transform.translation);
+        // later times stay put
+        let mut later = Transform::default();
+        movement.update(&mut later, movement.end_time() + 500.);
+        assert_eq!(later.translation, transform.translation);
+    }
+}
@@ -0,0 +1,381 @@
+//! Tweens for everything a piece has besides its position.
+//!
+//! Rotation and scale are `Transformer`s so they combine with movement through `Parallel`.
+//! Material color isn't part of the transform so `ColorTween` is its own component
+use bevy::prelude::*;
+
+use crate::hex::HexLayout;
+use super::{Eased, Easing, GameClock, Transformer};
+
+/// How far through start..end time is, from 0 to 1
+fn progress(time: f64, start_time: f64, end_time: f64) -> f32 {
+    let duration = end_time - start_time;
+    if duration > 0. {
+        ((time - start_time) / duration).clamp(0., 1.) as f32
+    } else {
+        1.
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Rotation ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+/// Turns from one rotation to another the short way around
+#[derive(Debug)]
+pub struct RotateTo {
+    start_time: f64,
+    end_time: f64,
+    start_rotation: Quat,
+    end_rotation: Quat
+}
+
+impl RotateTo {
+    /// duration: ms
+    pub fn new(start_rotation: Quat, end_rotation: Quat, duration: f64, start_time: f64) -> Self {
+        Self { start_time, end_time: start_time + duration, start_rotation, end_rotation }
+    }
+
+    /// Turns to face whichever of the six hex directions is closest to travel,
+    /// the world space offset the piece is about to move along
+    pub fn facing(start_rotation: Quat, travel: Vec3, layout: &HexLayout, duration: f64, start_time: f64) -> Self {
+        let direction = layout.nearest_direction(travel);
+        Self::new(start_rotation, layout.facing(direction), duration, start_time)
+    }
+}
+
+impl Transformer for RotateTo {
+    fn update(&self, transform: &mut Transform, time: f64) {
+        let t = progress(time, self.start_time, self.end_time);
+        transform.rotation = self.start_rotation.slerp(self.end_rotation, t);
+    }
+
+    fn start_time(&self) -> f64 {
+        self.start_time
+    }
+
+    fn end_time(&self) -> f64 {
+        self.end_time
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Scale ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+/// Grows or shrinks from one scale to another
+#[derive(Debug)]
+pub struct ScaleTo {
+    start_time: f64,
+    end_time: f64,
+    start_scale: Vec3,
+    end_scale: Vec3
+}
+
+impl ScaleTo {
+    /// duration: ms
+    pub fn new(start_scale: Vec3, end_scale: Vec3, duration: f64, start_time: f64) -> Self {
+        Self { start_time, end_time: start_time + duration, start_scale, end_scale }
+    }
+
+    /// Grows from nothing to scale, overshooting a little before settling. For spawning
+    pub fn pop_in(scale: Vec3, duration: f64, start_time: f64) -> Eased<Self> {
+        Self::new(Vec3::ZERO, scale, duration, start_time).eased(Easing::BackOut)
+    }
+
+    /// Swells a little then shrinks from scale to nothing. For despawning,
+    /// despawn once `TransformationFinished` comes in
+    pub fn pop_out(scale: Vec3, duration: f64, start_time: f64) -> Eased<Self> {
+        Self::new(scale, Vec3::ZERO, duration, start_time).eased(Easing::BackIn)
+    }
+}
+
+impl Transformer for ScaleTo {
+    fn update(&self, transform: &mut Transform, time: f64) {
+        let t = progress(time, self.start_time, self.end_time);
+        transform.scale = self.start_scale.lerp(self.end_scale, t);
+    }
+
+    fn start_time(&self) -> f64 {
+        self.start_time
+    }
+
+    fn end_time(&self) -> f64 {
+        self.end_time
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Color ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+/// Fades the `StandardMaterial` of an entity and its children between two colors.
+/// Removed once finished.
+///
+/// The first time an entity is tweened it gets its own copy of its material,
+/// so other entities that shared the material don't change with it
+#[derive(Component, Debug, Clone)]
+pub struct ColorTween {
+    start_time: f64,
+    end_time: f64,
+    start_color: Color,
+    end_color: Color,
+    easing: Easing,
+    /// go to end_color and back to start_color in the same time
+    round_trip: bool
+}
+
+impl ColorTween {
+    /// Fades from start_color to end_color. Fade alpha for ghosts
+    /// duration: ms
+    pub fn fade(start_color: Color, end_color: Color, duration: f64, start_time: f64) -> Self {
+        Self {
+            start_time,
+            end_time: start_time + duration,
+            start_color,
+            end_color,
+            easing: Easing::Linear,
+            round_trip: false
+        }
+    }
+
+    /// Flashes from color to flash_color and back. For damage
+    /// duration: ms
+    pub fn flash(color: Color, flash_color: Color, duration: f64, start_time: f64) -> Self {
+        Self {
+            round_trip: true,
+            ..Self::fade(color, flash_color, duration, start_time)
+        }
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Colors are mixed in linear space so fades don't dip darker in the middle
+    pub fn color_at(&self, time: f64) -> Color {
+        let mut t = progress(time, self.start_time, self.end_time);
+        if self.round_trip {
+            t = 1. - (2. * t - 1.).abs();
+        }
+        let t = self.easing.ease(t as f64) as f32;
+        let [r0, g0, b0, a0] = self.start_color.as_linear_rgba_f32();
+        let [r1, g1, b1, a1] = self.end_color.as_linear_rgba_f32();
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        Color::rgba_linear(
+            lerp(r0, r1),
+            lerp(g0, g1),
+            lerp(b0, b1),
+            lerp(a0, a1).clamp(0., 1.)
+        )
+    }
+
+    pub fn is_finished(&self, time: f64) -> bool {
+        time >= self.end_time
+    }
+}
+
+/// Marks an entity already given its own material, so later tweens keep using the copy
+#[derive(Component)]
+pub(super) struct OwnMaterial;
+
+/// Copies the material of each newly tweened entity and its children
+pub(super) fn own_tweened_materials(
+    mut commands: Commands,
+    query: Query<(Entity, Option<&Children>), Added<ColorTween>>,
+    mut handles: Query<&mut Handle<StandardMaterial>, Without<OwnMaterial>>,
+    // missing when running headless without rendering
+    materials: Option<ResMut<Assets<StandardMaterial>>>
+) {
+    let mut materials = match materials {
+        Some(materials) => materials,
+        None => return
+    };
+    for (entity, children) in query.iter() {
+        let mut targets = vec![entity];
+        if let Some(children) = children {
+            targets.extend(children.iter());
+        }
+        for target in targets {
+            let mut handle = match handles.get_mut(target) {
+                Ok(handle) => handle,
+                Err(_) => continue
+            };
+            if let Some(material) = materials.get(&handle).cloned() {
+                *handle = materials.add(material);
+                commands.entity(target).insert(OwnMaterial);
+            }
+        }
+    }
+}
+
+pub(super) fn color_tween_driver(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    query: Query<(Entity, &ColorTween, Option<&Children>)>,
+    handles: Query<&Handle<StandardMaterial>>,
+    // missing when running headless without rendering
+    materials: Option<ResMut<Assets<StandardMaterial>>>
+) {
+    let mut materials = match materials {
+        Some(materials) => materials,
+        None => return
+    };
+    let curr_time = clock.now();
+    for (entity, tween, children) in query.iter() {
+        let color = tween.color_at(curr_time);
+        // pieces keep their meshes on child entities
+        let mut targets = vec![entity];
+        if let Some(children) = children {
+            targets.extend(children.iter());
+        }
+        for target in targets {
+            let material = match handles.get(target) {
+                Ok(handle) => materials.get_mut(handle),
+                Err(_) => None
+            };
+            if let Some(material) = material {
+                material.base_color = color;
+                material.alpha_mode = if color.a() < 1. {AlphaMode::Blend} else {AlphaMode::Opaque};
+            }
+        }
+        if tween.is_finished(curr_time) {
+            commands.entity(entity).remove::<ColorTween>();
+        }
+    }
+}
+
+// ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ Tests ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~ //
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::AssetPlugin;
+
+    use super::*;
+    use crate::hex::{HexCoord, HexDirection, HexOrientation, WorldPlane};
+    use crate::plugins::world_3d::transformation::TransformationPlugin;
+
+    fn at(transformer: &impl Transformer, time: f64) -> Transform {
+        let mut transform = Transform::default();
+        transformer.update(&mut transform, time);
+        transform
+    }
+
+    fn assert_color(actual: Color, expected: Color) {
+        let actual = actual.as_linear_rgba_f32();
+        let expected = expected.as_linear_rgba_f32();
+        for (a, b) in actual.iter().zip(expected) {
+            assert!((a - b).abs() < 1e-5, "{:?} is not {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn rotate_to() {
+        let start = Quat::from_rotation_y(0.);
+        let end = Quat::from_rotation_y(1.);
+        let rotate = RotateTo::new(start, end, 10., 5.);
+        assert_eq!((rotate.start_time(), rotate.end_time()), (5., 15.));
+        assert!(at(&rotate, 5.).rotation.abs_diff_eq(start, 1e-6));
+        assert!(at(&rotate, 10.).rotation.abs_diff_eq(Quat::from_rotation_y(0.5), 1e-6));
+        assert!(at(&rotate, 15.).rotation.abs_diff_eq(end, 1e-6));
+        assert!(at(&rotate, 30.).rotation.abs_diff_eq(end, 1e-6));
+    }
+
+    #[test]
+    fn rotate_to_faces_the_nearest_direction() {
+        let layout = HexLayout::new(HexOrientation::Pointy, 1., WorldPlane::XZ);
+        let origin = HexCoord(0, 0);
+        for direction in HexDirection::ALL {
+            let travel = layout.to_world(origin.neighbor(direction)) - layout.to_world(origin);
+            // a little off the straight line still picks the same side
+            let nudged = travel + Vec3::new(0.1, 0., -0.05);
+            let rotate = RotateTo::facing(Quat::IDENTITY, nudged, &layout, 10., 0.);
+            assert!(at(&rotate, 0.).rotation.abs_diff_eq(Quat::IDENTITY, 1e-6));
+            assert!(at(&rotate, 10.).rotation.abs_diff_eq(layout.facing(direction), 1e-6), "{:?}", direction);
+        }
+    }
+
+    #[test]
+    fn scale_to() {
+        let scale = ScaleTo::new(Vec3::ONE, Vec3::splat(3.), 10., 0.);
+        assert_eq!(at(&scale, 0.).scale, Vec3::ONE);
+        assert_eq!(at(&scale, 5.).scale, Vec3::splat(2.));
+        assert_eq!(at(&scale, 10.).scale, Vec3::splat(3.));
+    }
+
+    #[test]
+    fn pop_in_overshoots_then_settles() {
+        let size = Vec3::splat(2.);
+        let pop = ScaleTo::pop_in(size, 10., 0.);
+        assert!(at(&pop, 0.).scale.abs_diff_eq(Vec3::ZERO, 1e-6));
+        assert!(at(&pop, 7.).scale.x > size.x);
+        assert!(at(&pop, 10.).scale.abs_diff_eq(size, 1e-6));
+    }
+
+    #[test]
+    fn pop_out_swells_then_vanishes() {
+        let size = Vec3::splat(2.);
+        let pop = ScaleTo::pop_out(size, 10., 0.);
+        assert!(at(&pop, 0.).scale.abs_diff_eq(size, 1e-6));
+        assert!(at(&pop, 2.).scale.x > size.x);
+        assert!(at(&pop, 10.).scale.abs_diff_eq(Vec3::ZERO, 1e-6));
+    }
+
+    #[test]
+    fn fade() {
+        let fade = ColorTween::fade(Color::WHITE, Color::rgba(1., 1., 1., 0.), 10., 0.);
+        assert_color(fade.color_at(0.), Color::WHITE);
+        assert_color(fade.color_at(5.), Color::rgba(1., 1., 1., 0.5));
+        assert_color(fade.color_at(10.), Color::rgba(1., 1., 1., 0.));
+        assert!(!fade.is_finished(9.));
+        assert!(fade.is_finished(10.));
+    }
+
+    #[test]
+    fn fade_mixes_in_linear_space() {
+        let fade = ColorTween::fade(Color::BLACK, Color::WHITE, 10., 0.);
+        assert_color(fade.color_at(5.), Color::rgba_linear(0.5, 0.5, 0.5, 1.));
+    }
+
+    #[test]
+    fn flash_goes_there_and_back() {
+        let flash = ColorTween::flash(Color::WHITE, Color::RED, 10., 0.);
+        assert_color(flash.color_at(0.), Color::WHITE);
+        assert_color(flash.color_at(5.), Color::RED);
+        assert_color(flash.color_at(10.), Color::WHITE);
+    }
+
+    fn material_color(app: &App, entity: Entity) -> Color {
+        let handle = app.world.get::<Handle<StandardMaterial>>(entity).unwrap();
+        app.world.resource::<Assets<StandardMaterial>>().get(handle).unwrap().base_color
+    }
+
+    #[test]
+    fn tweens_a_copy_of_the_material() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default())
+            .add_asset::<StandardMaterial>()
+            .add_plugin(TransformationPlugin);
+        app.world.resource_mut::<GameClock>().pause();
+
+        let shared = app.world.resource_mut::<Assets<StandardMaterial>>().add(Color::WHITE.into());
+        let mesh = app.world.spawn(shared.clone()).id();
+        let bystander = app.world.spawn(shared.clone()).id();
+        let piece = app.world.spawn(ColorTween::fade(Color::WHITE, Color::BLACK, 10., 0.)).id();
+        app.world.entity_mut(piece).push_children(&[mesh]);
+
+        app.update();
+        assert_ne!(app.world.get::<Handle<StandardMaterial>>(mesh), Some(&shared));
+        app.world.resource_mut::<GameClock>().advance(5.);
+        app.update();
+        assert_color(material_color(&app, mesh), Color::rgba_linear(0.5, 0.5, 0.5, 1.));
+        assert_color(material_color(&app, bystander), Color::WHITE);
+
+        app.world.resource_mut::<GameClock>().advance(5.);
+        app.update();
+        assert_color(material_color(&app, mesh), Color::BLACK);
+        assert!(app.world.get::<ColorTween>(piece).is_none());
+
+        // tweening again keeps the same copy
+        let copy = app.world.get::<Handle<StandardMaterial>>(mesh).unwrap().clone();
+        app.world.entity_mut(piece).insert(ColorTween::flash(Color::BLACK, Color::RED, 10., 10.));
+        app.update();
+        assert_eq!(app.world.get::<Handle<StandardMaterial>>(mesh), Some(&copy));
+    }
+}